@@ -0,0 +1,138 @@
+// key/value attributes on nodes and edges
+//
+// Metadata only: the analysis never reads attributes, but queries and reports hand them back
+// so a Divergent edge can be traced to e.g. its source file and line.
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::types::{AttrValue, Attributes, EdgeId, NodeId};
+
+impl ReflexionGraph {
+    pub fn node_attrs(&self, node: NodeId) -> Result<&Attributes, GraphError> {
+        self.nodes.get(&node).map(|n| &n.attrs).ok_or(GraphError::NodeNotFound(node))
+    }
+
+    pub fn node_attr(&self, node: NodeId, key: &str) -> Result<Option<&AttrValue>, GraphError> {
+        Ok(self.node_attrs(node)?.get(key))
+    }
+
+    //returns the previous value
+    pub fn set_node_attr(
+        &mut self,
+        node: NodeId,
+        key: impl Into<String>,
+        value: impl Into<AttrValue>,
+    ) -> Result<Option<AttrValue>, GraphError> {
+        let n = self.nodes.get_mut(&node).ok_or(GraphError::NodeNotFound(node))?;
+        Ok(n.attrs.insert(key.into(), value.into()))
+    }
+
+    pub fn remove_node_attr(&mut self, node: NodeId, key: &str) -> Result<Option<AttrValue>, GraphError> {
+        let n = self.nodes.get_mut(&node).ok_or(GraphError::NodeNotFound(node))?;
+        Ok(n.attrs.remove(key))
+    }
+
+    pub fn edge_attrs(&self, edge: EdgeId) -> Result<&Attributes, GraphError> {
+        self.edges.get(&edge).map(|e| &e.attrs).ok_or(GraphError::EdgeNotFound(edge))
+    }
+
+    pub fn edge_attr(&self, edge: EdgeId, key: &str) -> Result<Option<&AttrValue>, GraphError> {
+        Ok(self.edge_attrs(edge)?.get(key))
+    }
+
+    //returns the previous value
+    pub fn set_edge_attr(
+        &mut self,
+        edge: EdgeId,
+        key: impl Into<String>,
+        value: impl Into<AttrValue>,
+    ) -> Result<Option<AttrValue>, GraphError> {
+        let e = self.edges.get_mut(&edge).ok_or(GraphError::EdgeNotFound(edge))?;
+        Ok(e.attrs.insert(key.into(), value.into()))
+    }
+
+    pub fn remove_edge_attr(&mut self, edge: EdgeId, key: &str) -> Result<Option<AttrValue>, GraphError> {
+        let e = self.edges.get_mut(&edge).ok_or(GraphError::EdgeNotFound(edge))?;
+        Ok(e.attrs.remove(key))
+    }
+
+    //nodes whose attribute `key` equals `value` (e.g. owner == "payments"), ordered by id
+    pub fn nodes_with_attr(&self, key: &str, value: &AttrValue) -> Vec<NodeId> {
+        let mut out: Vec<NodeId> = self
+            .nodes
+            .values()
+            .filter(|n| n.attrs.get(key) == Some(value))
+            .map(|n| n.id)
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //edges whose attribute `key` equals `value` (e.g. file == "src/ui/login.rs"), ordered by id
+    pub fn edges_with_attr(&self, key: &str, value: &AttrValue) -> Vec<EdgeId> {
+        let mut out: Vec<EdgeId> = self
+            .edges
+            .values()
+            .filter(|e| e.attrs.get(key) == Some(value))
+            .map(|e| e.id)
+            .collect();
+        out.sort_unstable();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::types::{AttrValue, EdgeKind, SubgraphKind};
+
+    #[test]
+    fn node_and_edge_attributes_round_trip() {
+        let mut g = ReflexionGraph::new();
+        let a = g
+            .add_node(Node::new("LoginPage", SubgraphKind::Implementation, None).with_attr("owner", "web"))
+            .unwrap();
+        let b = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_impl_edge(a, b, EdgeKind::calls()).unwrap();
+
+        assert_eq!(g.node_attr(a, "owner").unwrap(), Some(&AttrValue::from("web")));
+        assert_eq!(g.set_node_attr(b, "owner", "core").unwrap(), None);
+        assert_eq!(g.set_node_attr(b, "owner", "platform").unwrap(), Some(AttrValue::from("core")));
+
+        g.set_edge_attr(e, "file", "src/ui/login.rs").unwrap();
+        g.set_edge_attr(e, "line", 42i64).unwrap();
+        g.set_edge_attr(e, "weight", 0.5).unwrap();
+        g.set_edge_attr(e, "generated", false).unwrap();
+        assert_eq!(g.edge_attr(e, "line").unwrap().and_then(AttrValue::as_int), Some(42));
+        assert_eq!(g.edge_attr(e, "weight").unwrap().and_then(AttrValue::as_float), Some(0.5));
+        assert_eq!(g.edge_attrs(e).unwrap().len(), 4);
+
+        assert_eq!(g.remove_edge_attr(e, "generated").unwrap(), Some(AttrValue::Bool(false)));
+        assert_eq!(g.remove_node_attr(a, "missing").unwrap(), None);
+        assert_eq!(g.set_edge_attr(99, "x", 1i64), Err(GraphError::EdgeNotFound(99)));
+        assert_eq!(g.node_attrs(99), Err(GraphError::NodeNotFound(99)));
+    }
+
+    #[test]
+    fn attribute_queries_and_violation_report() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let store = g.add_node(Node::new("UserStore", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(store, db).unwrap();
+        g.set_node_attr(page, "owner", "web").unwrap();
+
+        let e = g.add_impl_edge(page, store, EdgeKind::calls()).unwrap();
+        g.set_edge_attr(e, "file", "src/ui/login.rs").unwrap();
+        g.set_edge_attr(e, "line", 7i64).unwrap();
+
+        assert_eq!(g.nodes_with_attr("owner", &"web".into()), vec![page]);
+        assert_eq!(g.edges_with_attr("file", &"src/ui/login.rs".into()), vec![e]);
+
+        //the divergent impl edge is reported together with its source location
+        let report = g.violations();
+        let v = report.iter().find(|v| v.edge == e).unwrap();
+        assert_eq!(v.attrs.get("line"), Some(&AttrValue::Int(7)));
+        assert_eq!((v.from, v.to), (page, store));
+    }
+}
@@ -0,0 +1,154 @@
+// public builders, the way for downstream crates to populate a graph
+//
+//   let ui = NodeBuilder::new("UI", SubgraphKind::Architecture).add_to(&mut g)?;
+//   let e = EdgeBuilder::new(page, svc).kind(EdgeKind::calls()).attr("line", 12i64).add_to(&mut g)?;
+//
+// Edges go through the incremental API (add_impl_edge / add_arch_edge / add_allowed_edge), so
+// a graph built this way is analysed as it grows; run_from_scratch is never required.
+use crate::core::graph::{GraphError, Node, ReflexionGraph};
+use crate::core::types::{AttrValue, Attributes, EdgeId, EdgeKind, NodeId, NodeKind, SubgraphKind};
+
+#[derive(Debug, Clone)]
+pub struct NodeBuilder {
+    node: Node,
+}
+
+impl NodeBuilder {
+    pub fn new(name: impl Into<String>, subgraph: SubgraphKind) -> Self {
+        Self { node: Node::new(name, subgraph, None) }
+    }
+
+    pub fn architecture(name: impl Into<String>) -> Self {
+        Self::new(name, SubgraphKind::Architecture)
+    }
+
+    pub fn implementation(name: impl Into<String>) -> Self {
+        Self::new(name, SubgraphKind::Implementation)
+    }
+
+    pub fn parent(mut self, parent: NodeId) -> Self {
+        self.node.parent = Some(parent);
+        self
+    }
+
+    pub fn kind(mut self, kind: NodeKind) -> Self {
+        self.node.kind = kind;
+        self
+    }
+
+    pub fn attr(mut self, key: impl Into<String>, value: impl Into<AttrValue>) -> Self {
+        self.node.attrs.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Node {
+        self.node
+    }
+
+    pub fn add_to(self, g: &mut ReflexionGraph) -> Result<NodeId, GraphError> {
+        g.add_node(self.node)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgeBuilder {
+    from: NodeId,
+    to: NodeId,
+    kind: EdgeKind,
+    subgraph: SubgraphKind,
+    allowed: bool,
+    attrs: Attributes,
+}
+
+impl EdgeBuilder {
+    //implementation edge of kind depends_on unless told otherwise
+    pub fn new(from: NodeId, to: NodeId) -> Self {
+        Self {
+            from,
+            to,
+            kind: EdgeKind::depends_on(),
+            subgraph: SubgraphKind::Implementation,
+            allowed: false,
+            attrs: Attributes::new(),
+        }
+    }
+
+    pub fn kind(mut self, kind: impl Into<EdgeKind>) -> Self {
+        self.kind = kind.into();
+        self
+    }
+
+    //specified architecture edge
+    pub fn architecture(mut self) -> Self {
+        self.subgraph = SubgraphKind::Architecture;
+        self.allowed = false;
+        self
+    }
+
+    //allowed (permitted but not required) architecture edge
+    pub fn allowed(mut self) -> Self {
+        self.subgraph = SubgraphKind::Architecture;
+        self.allowed = true;
+        self
+    }
+
+    pub fn attr(mut self, key: impl Into<String>, value: impl Into<AttrValue>) -> Self {
+        self.attrs.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn add_to(self, g: &mut ReflexionGraph) -> Result<EdgeId, GraphError> {
+        let id = match (self.subgraph, self.allowed) {
+            (SubgraphKind::Architecture, true) => g.add_allowed_edge(self.from, self.to, self.kind)?,
+            (SubgraphKind::Architecture, false) => g.add_arch_edge(self.from, self.to, self.kind)?,
+            _ => g.add_impl_edge(self.from, self.to, self.kind)?,
+        };
+        g.edges.get_mut(&id).expect("just added").attrs = self.attrs;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EdgeBuilder, NodeBuilder};
+    use crate::core::graph::{GraphError, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{AttrValue, EdgeKind, NodeKind, SubgraphKind};
+
+    #[test]
+    fn builders_create_nodes_and_analysed_edges() {
+        let mut g = ReflexionGraph::new();
+        let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+        let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+        let pkg = NodeBuilder::implementation("ui").add_to(&mut g).unwrap();
+        let page = NodeBuilder::implementation("LoginPage")
+            .parent(pkg)
+            .kind(NodeKind::UINode)
+            .attr("file", "src/ui/login.rs")
+            .add_to(&mut g)
+            .unwrap();
+        let svc = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+        g.set_mapping(pkg, ui).unwrap();
+        g.set_mapping(svc, service).unwrap();
+
+        let arch = EdgeBuilder::new(ui, service).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+        let e = EdgeBuilder::new(page, svc).kind("calls").attr("line", 12i64).add_to(&mut g).unwrap();
+
+        let node = g.node(page).unwrap();
+        assert_eq!(node.parent(), Some(pkg));
+        assert_eq!(node.kind(), &NodeKind::UINode);
+        assert_eq!(g.node(pkg).unwrap().children(), &[page]);
+
+        let edge = g.edge(e).unwrap();
+        assert_eq!((edge.from(), edge.to(), edge.subgraph()), (page, svc, SubgraphKind::Implementation));
+        assert_eq!(edge.state(), EdgeState::Convergent);
+        assert_eq!(edge.attrs().get("line"), Some(&AttrValue::Int(12)));
+        assert_eq!(g.edge_counter(arch).unwrap(), 1);
+
+        //an allowed edge to a non-architecture node is rejected like add_allowed_edge would
+        assert!(matches!(
+            EdgeBuilder::new(ui, page).allowed().add_to(&mut g),
+            Err(GraphError::WrongSubgraph { .. })
+        ));
+    }
+}
@@ -1,27 +1,43 @@
 // classification logic
-use std::collections::HashSet;
-use std::ops::Sub;
-use crate::core::graph::{GraphError, ReflexionGraph, Edge};
-use crate::core::state::EdgeState;
-use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::{EdgeState, Severity};
+use crate::core::types::{Attributes, EdgeId, EdgeKind, NodeId, SubgraphKind};
+
+//one violating edge, with its attributes so it can be traced back (file, line, owner, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub edge: EdgeId,
+    pub subgraph: SubgraphKind,
+    pub from: NodeId,
+    pub to: NodeId,
+    pub kind: EdgeKind,
+    pub state: EdgeState,
+    pub attrs: Attributes,
+}
 
 
 impl ReflexionGraph {
     //Run a full reflexion analysis from scratch:
-    // - Clears old propagated edges
+    // - Clears old propagated edges (the run hands their ids out again)
     // - Resets states/counters
     // - Propagates + lifts every impl edge
     // - Finalizes arch edge states (Absent/Convergent normalization)
 
     pub fn run_from_scratch(&mut self) -> Result<(), GraphError> {
         //if we ran before, we must drop old propagated edges, otherwise stale edges can survive
-        self.clear_propagated_edges();
+        self.recycle_propagated_edges();
+        let result = self.analyse_all_edges();
+        //ids this run did not take again stay unused
+        self.free_propagated_ids.clear();
+        result
+    }
 
+    fn analyse_all_edges(&mut self) -> Result<(), GraphError> {
         //reset counters/states
         self.init_states();
 
         //collect implementation edges 
-        let impl_edge_ids: Vec<EdgeId> = self.edges.iter().filter_map(|(&eid, e)| {
+        let impl_edge_ids: Vec<EdgeId> = self.edges.iter().filter_map(|(eid, e)| {
             if e.subgraph == SubgraphKind::Implementation {
                 Some(eid)
             } else {
@@ -30,7 +46,7 @@ impl ReflexionGraph {
         }).collect();
 
         for eid in impl_edge_ids {
-            self.propagate_and_lift(eid);
+            self.propagate_and_lift(eid)?;
         }
 
         self.finalize_architecture_states();
@@ -38,40 +54,67 @@ impl ReflexionGraph {
     }
 
     // Normalize final architecture edge states after propagation+lifting:
-    // - Specified + counter==0  -> Absent
-    // - Specified + counter>0   -> Convergent  (defensive normalization)
+    // - Specified + counter==0  -> Absent      (allowed edge: AllowedAbsent)
+    // - Specified + counter>0   -> Convergent  (allowed edge: Allowed; defensive normalization)
     pub fn finalize_architecture_states(&mut self) {
+        let allowed = &self.allowed_edges;
         for e in self.edges.values_mut() {
             if e.subgraph != SubgraphKind::Architecture {
                 continue;
             }
 
+            let is_allowed = allowed.contains(&e.id);
             if matches!(e.state, EdgeState::Specified) && e.counter == 0 {
-                e.state = EdgeState::Absent;
+                e.state = if is_allowed { EdgeState::AllowedAbsent } else { EdgeState::Absent };
             } else if matches!(e.state, EdgeState::Specified) && e.counter > 0 {
                 // If lifting forgot to flip it, finalize makes it consistent.
-                e.state = EdgeState::Convergent;
+                e.state = if is_allowed { EdgeState::Allowed } else { EdgeState::Convergent };
             }
         }
     }
 
+    //all violations, forbidden edges included
     pub fn count_violations(&self) -> usize {
         self.edges.values().filter(|e| e.state.is_violation()).count()
     }
+
+    //every violating edge, ordered by id
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut out: Vec<Violation> = self
+            .edges
+            .values()
+            .filter(|e| e.state.is_violation())
+            .map(|e| Violation {
+                edge: e.id,
+                subgraph: e.subgraph,
+                from: e.from,
+                to: e.to,
+                kind: self.resolve_kind(e.kind).clone(),
+                state: e.state,
+                attrs: e.attrs.clone(),
+            })
+            .collect();
+        out.sort_by_key(|v| v.edge);
+        out
+    }
+
+    //only hard policy breaches
+    pub fn count_forbidden(&self) -> usize {
+        self.edges.values().filter(|e| e.state.severity() == Severity::PolicyBreach).count()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::core::graph::{Edge, Node, ReflexionGraph};
     use crate::core::state::EdgeState;
-    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+    use crate::core::types::{EdgeKind, KindId, NodeId, SubgraphKind};
 
     fn mk_node(name: &str, subgraph: SubgraphKind, parent: Option<NodeId>) -> Node {
         Node::new(name.to_string(), subgraph, parent)
     }
 
-    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: EdgeKind) -> Edge {
+    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: KindId) -> Edge {
         Edge {
             id: 0, // overwritten by add_edge
             from,
@@ -80,11 +123,12 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         }
     }
 
     #[test]
-    fn run_from_scratch_marks_convergent_allowed_and_no_divergence() {
+    fn run_from_scratch_marks_convergent_and_no_divergence() {
         let mut g = ReflexionGraph::new();
 
         //architecture nodes
@@ -105,7 +149,7 @@ mod tests {
 
         //arch edge UI -> Service (specified)
         let arch_eid = g
-            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, EdgeKind::calls()))
+            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::CALLS))
             .unwrap();
 
         //impl edge LoginPage -> UserService
@@ -114,7 +158,7 @@ mod tests {
                 login_page,
                 user_service,
                 SubgraphKind::Implementation,
-                EdgeKind::calls(),
+                KindId::CALLS,
             ))
             .unwrap();
 
@@ -130,9 +174,9 @@ mod tests {
         assert!(matches!(arch_edge.state, EdgeState::Convergent));
         assert_eq!(arch_edge.counter, 1);
 
-        //assert impl edge allowed (because it lifted onto specified arch edge)
+        //assert impl edge convergent (because it lifted onto specified arch edge)
         let impl_edge = g.edges.get(&impl_eid).unwrap();
-        assert!(matches!(impl_edge.state, EdgeState::Allowed));
+        assert!(matches!(impl_edge.state, EdgeState::Convergent));
 
         //assert no divergent edges anywhere
         let has_divergent = g.edges.values().any(|e| matches!(e.state, EdgeState::Divergent));
@@ -159,11 +203,11 @@ mod tests {
             .unwrap();
 
         let e_arch = g
-            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, EdgeKind::depends_on()))
+            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::DEPENDS_ON))
             .unwrap();
 
         //no impl edges at all
-        g.run_from_scratch();
+        g.run_from_scratch().unwrap();
 
         let arch_e = g.edges.get(&e_arch).unwrap();
         assert!(matches!(arch_e.state, EdgeState::Absent));
@@ -187,7 +231,7 @@ mod tests {
 
         //only specified edge: UI -> Service
         let _e_arch = g
-            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, EdgeKind::depends_on()))
+            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::DEPENDS_ON))
             .unwrap();
 
         //impl nodes
@@ -199,8 +243,8 @@ mod tests {
             .unwrap();
 
         //mapping: LoginPage -> UI, DBClient -> DB
-        g.set_mapping_overwrite(login, ui);
-        g.set_mapping_overwrite(db_impl, db);
+        g.set_mapping_overwrite(login, ui).unwrap();
+        g.set_mapping_overwrite(db_impl, db).unwrap();
 
         //impl edge: LoginPage -> DBClient (mapped to UI -> DB), which is NOT specified
         let e_impl = g
@@ -208,11 +252,11 @@ mod tests {
                 login,
                 db_impl,
                 SubgraphKind::Implementation,
-                EdgeKind::depends_on(),
+                KindId::DEPENDS_ON,
             ))
             .unwrap();
 
-        g.run_from_scratch();
+        g.run_from_scratch().unwrap();
 
         let impl_e = g.edges.get(&e_impl).unwrap();
         assert!(matches!(impl_e.state, EdgeState::Divergent));
@@ -238,15 +282,83 @@ mod tests {
                 a,
                 b,
                 SubgraphKind::Implementation,
-                EdgeKind::depends_on(),
+                KindId::DEPENDS_ON,
             ))
             .unwrap();
 
-        g.run_from_scratch();
+        g.run_from_scratch().unwrap();
 
         let impl_e = g.edges.get(&e_impl).unwrap();
         assert!(matches!(impl_e.state, EdgeState::Unmapped));
     }
-}
 
+    ///allowed edges: permitted but not required.
+    ///expected: implemented -> Allowed everywhere, not implemented -> AllowedAbsent, no violations.
+    #[test]
+    fn allowed_edges_yield_allowed_and_allowed_absent() {
+        let mut g = ReflexionGraph::new();
+
+        let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(mk_node("Service", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(mk_node("DB", SubgraphKind::Architecture, None)).unwrap();
+
+        let e_used = g.add_allowed_edge(ui, service, EdgeKind::calls()).unwrap();
+        let e_unused = g.add_allowed_edge(ui, db, EdgeKind::calls()).unwrap();
+
+        let login = g.add_node(mk_node("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(mk_node("UserService", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(login, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        let e_impl = g
+            .add_edge(mk_edge(login, usersvc, SubgraphKind::Implementation, KindId::CALLS))
+            .unwrap();
+
+        g.run_from_scratch().unwrap();
+
+        let used = g.edges.get(&e_used).unwrap();
+        assert!(matches!(used.state, EdgeState::Allowed));
+        assert_eq!(used.counter, 1);
+        assert!(matches!(g.edges.get(&e_unused).unwrap().state, EdgeState::AllowedAbsent));
+        assert!(matches!(g.edges.get(&e_impl).unwrap().state, EdgeState::Allowed));
+        assert!(g
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Propagated)
+            .all(|e| matches!(e.state, EdgeState::Allowed)));
+        assert_eq!(g.count_violations(), 0);
+
+        //a specified edge with the same (from, to, kind) takes precedence
+        let e_spec = g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&e_spec).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(g.edges.get(&e_used).unwrap().state, EdgeState::AllowedAbsent));
+        assert!(matches!(g.edges.get(&e_impl).unwrap().state, EdgeState::Convergent));
+    }
+
+    #[test]
+    fn repeated_full_runs_reuse_propagated_edge_ids() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(mk_node("Service", SubgraphKind::Architecture, None)).unwrap();
+        let login = g.add_node(mk_node("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(mk_node("UserService", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(login, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        g.add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+        let e_impl = g
+            .add_edge(mk_edge(login, usersvc, SubgraphKind::Implementation, KindId::CALLS))
+            .unwrap();
 
+        g.run_from_scratch().unwrap();
+        let prop = g.propagated_edge_of(e_impl).unwrap();
+        let capacity = g.edges.capacity();
+        for _ in 0..1000 {
+            g.run_from_scratch().unwrap();
+            g.run_from_scratch_parallel(2).unwrap();
+        }
+        assert_eq!(g.edges.len(), 3);
+        assert_eq!(g.edges.capacity(), capacity);
+        assert_eq!(g.propagated_edge_of(e_impl), Some(prop));
+        assert!(g.free_propagated_ids.is_empty());
+    }
+}
@@ -1,80 +1,475 @@
 // incremental diffs
-use std::collections::HashSet;
-use std::ops::Sub;
 use crate::core::graph::{GraphError, ReflexionGraph, Edge};
 use crate::core::state::EdgeState;
-use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind};
+use crate::core::types::{EdgeId, NodeId, RuleId, SubgraphKind, EdgeKind, KindId};
 
 impl ReflexionGraph {
-    /// Naive incremental: insert an implementation edge then recompute everything.
+    /// Delta-based incremental: insert an implementation edge and propagate only that edge.
     ///
-    /// Correct but not efficient. This is an API skeleton that will later be replaced
-    /// by a real delta-based incremental algorithm.
-    pub fn add_impl_edge_and_recompute(&mut self, edge: Edge) -> Result<EdgeId, GraphError> {
-        if edge.subgraph != SubgraphKind::Implementation {
-            return Err(GraphError::WrongSubgraph {
-                node: edge.from, // best available identifier here
-                expected: SubgraphKind::Implementation,
-                found: edge.subgraph,
-            });
+    /// Assumes the graph has already been analysed (`run_from_scratch`). The new edge is
+    /// propagated + lifted on its own, which touches exactly one propagated edge (counter++)
+    /// and at most one architecture edge (counter++, Absent -> Convergent). Everything else
+    /// is left alone, and the result is the same as rerunning the whole analysis.
+    pub fn add_impl_edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind) -> Result<EdgeId, GraphError> {
+        let kind = self.kinds.intern(&kind);
+        self.insert_impl_edge(Edge {
+            id: 0, // overwritten by add_edge
+            from,
+            to,
+            kind,
+            subgraph: SubgraphKind::Implementation,
+            state: EdgeState::Undefined,
+            counter: 0,
+            attrs: Default::default(),
+        })
+    }
+
+    //add_edge + propagate_and_lift; both endpoints must be implementation nodes
+    fn insert_impl_edge(&mut self, edge: Edge) -> Result<EdgeId, GraphError> {
+        for node in [edge.from, edge.to] {
+            let found = self.node_subgraph(node)?;
+            if found != SubgraphKind::Implementation {
+                return Err(GraphError::WrongSubgraph {
+                    node,
+                    expected: SubgraphKind::Implementation,
+                    found,
+                });
+            }
         }
 
         let id = self.add_edge(edge)?;
+        self.propagate_and_lift(id)?;
+        Ok(id)
+    }
+
+    /// Counter-based incremental: remove an implementation edge without rerunning the analysis.
+    ///
+    /// The propagated edge it contributed to (found via the reverse index `impl_to_prop`) and
+    /// the architecture edge that propagated edge lifted to are both decremented. A propagated
+    /// edge whose counter drops to zero is deleted; an architecture edge whose counter drops
+    /// to zero goes back from Convergent to Absent.
+    pub fn remove_impl_edge(&mut self, edge_id: EdgeId) -> Result<(), GraphError> {
+        self.withdraw_impl_edge(edge_id)?;
+        self.detach_edge(edge_id);
+        Ok(())
+    }
 
-        // Avoid stale propagated edges / counters accumulating across runs.
-        self.clear_propagated_edges();
-        self.run_from_scratch();
+    /// Reverse of `propagate_and_lift` for one implementation edge.
+    ///
+    /// The edge itself stays in the graph (state reset to Undefined), only its contribution to
+    /// propagated/architecture edges is taken back. Unmapped edges never contributed anything.
+    pub fn withdraw_impl_edge(&mut self, impl_edge_id: EdgeId) -> Result<(), GraphError> {
+        {
+            let e = self
+                .edges
+                .get(&impl_edge_id)
+                .ok_or(GraphError::EdgeNotFound(impl_edge_id))?;
 
-        Ok(id)
+            if e.subgraph != SubgraphKind::Implementation {
+                return Err(GraphError::WrongSubgraph {
+                    node: e.from,
+                    expected: SubgraphKind::Implementation,
+                    found: e.subgraph,
+                });
+            }
+        }
+
+        //1) which propagated edge did this impl edge feed into (if any)
+        let prop_id = self.impl_to_prop.remove(&impl_edge_id);
+
+        if let Some(prop_id) = prop_id {
+            if let Some(impls) = self.propagation_table.get_mut(&prop_id) {
+                impls.remove(&impl_edge_id);
+            }
+
+            //2) decrement propagated edge
+            let remaining = {
+                let pe = self.edges.get_mut(&prop_id).ok_or(GraphError::EdgeNotFound(prop_id))?;
+                pe.counter -= 1;
+                pe.counter
+            };
+
+            //3) decrement the architecture edge it lifted to
+            if let Some(arch_eid) = self.lifted_to.get(&prop_id).copied() {
+                let absent = self.unimplemented_state(arch_eid);
+                if let Some(ae) = self.edges.get_mut(&arch_eid) {
+                    ae.counter -= 1;
+                    if ae.counter == 0 {
+                        ae.state = absent;
+                    }
+                }
+            }
+
+            //4) no impl edge left behind it -> the propagated edge disappears
+            if remaining == 0 {
+                self.detach_edge(prop_id);
+            }
+        }
+
+        if let Some(ie) = self.edges.get_mut(&impl_edge_id) {
+            ie.state = EdgeState::Undefined;
+        }
+
+        Ok(())
     }
 
-    /// Naive incremental: remove an implementation edge then recompute everything.
-    pub fn remove_impl_edge_and_recompute(&mut self, edge_id: EdgeId) -> Result<(), GraphError> {
-        let e = self
+    /// Incremental `set_mapping`: map `impl_node` and update the analysis right away.
+    ///
+    /// Same rules as `set_mapping` (no silent overwrites). Every implementation edge incident
+    /// to `impl_node` is withdrawn from its old propagated edge and re-propagated under the
+    /// new mapping. With implicit mapping this includes the edges of every descendant that
+    /// inherits its mapping from `impl_node`, so mapping a package covers the whole subtree.
+    pub fn set_mapping_incremental(&mut self, impl_node: NodeId, arch_node: NodeId) -> Result<(), GraphError> {
+        let affected = self.mapping_affected_edges(impl_node);
+        self.with_edges_withdrawn(affected, |g| g.set_mapping(impl_node, arch_node))
+    }
+
+    /// Incremental `set_mapping_overwrite`; returns the previous mapping like the plain version.
+    pub fn set_mapping_overwrite_incremental(
+        &mut self,
+        impl_node: NodeId,
+        arch_node: NodeId,
+    ) -> Result<Option<NodeId>, GraphError> {
+        let affected = self.mapping_affected_edges(impl_node);
+        self.with_edges_withdrawn(affected, |g| g.set_mapping_overwrite(impl_node, arch_node))
+    }
+
+    /// Incremental `remove_mapping`: the incident edges end up Unmapped.
+    pub fn remove_mapping_incremental(&mut self, impl_node: NodeId) -> Result<Option<NodeId>, GraphError> {
+        let affected = self.mapping_affected_edges(impl_node);
+        self.with_edges_withdrawn(affected, |g| g.remove_mapping(impl_node))
+    }
+
+    /// Incremental `clear_mappings`: every implementation edge is affected, so this is as
+    /// expensive as a full run, but it leaves the analysis consistent.
+    pub fn clear_mappings_incremental(&mut self) -> Result<(), GraphError> {
+        let mut affected: Vec<EdgeId> = self
             .edges
-            .get(&edge_id)
-            .ok_or(GraphError::EdgeNotFound(edge_id))?;
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Implementation)
+            .map(|e| e.id)
+            .collect();
+        affected.sort_unstable();
 
-        if e.subgraph != SubgraphKind::Implementation {
-            return Err(GraphError::WrongSubgraph {
-                node: e.from,
-                expected: SubgraphKind::Implementation,
-                found: e.subgraph,
-            });
+        self.with_edges_withdrawn(affected, |g| {
+            g.clear_mappings();
+            Ok(())
+        })
+    }
+
+    //impl edges whose propagation depends on impl_node's own maps_to entry
+    fn mapping_affected_edges(&self, impl_node: NodeId) -> Vec<EdgeId> {
+        self.impl_edges_touching(&self.inheriting_nodes(impl_node))
+    }
+
+    //Move the implementation edges behind candidate propagated edges (chosen by kind) whose
+    //propagation kind no longer matches, after a kind became used or unused or got a supertype.
+    pub(crate) fn regroup_propagated_edges(&mut self, candidate: impl Fn(&Self, KindId) -> bool) -> Result<(), GraphError> {
+        let g = &*self;
+        let mut moved: Vec<EdgeId> = g
+            .prop_index
+            .iter()
+            .filter(|&(&(_, _, kind), _)| candidate(g, kind))
+            .flat_map(|(&(_, _, kind), prop_id)| {
+                g.propagation_table
+                    .get(prop_id)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(move |ie| g.edges.get(ie).is_some_and(|e| g.propagation_kind(e.kind) != kind))
+            })
+            .collect();
+        if moved.is_empty() {
+            return Ok(());
+        }
+        moved.sort_unstable();
+        self.with_edges_withdrawn(moved, |_| Ok(()))
+    }
+
+    //Withdraw `edges`, apply a mapping change, then propagate + lift the same edges again.
+    //Every edge that was withdrawn is re-propagated even when `op`, another withdrawal or
+    //another re-propagation fails, so an error never leaves the analysis half-withdrawn. Only
+    //an edge whose own propagate_and_lift fails stays withdrawn (Undefined). The first error
+    //is returned.
+    fn with_edges_withdrawn<T>(
+        &mut self,
+        edges: Vec<EdgeId>,
+        op: impl FnOnce(&mut Self) -> Result<T, GraphError>,
+    ) -> Result<T, GraphError> {
+        let mut withdrawn = Vec::with_capacity(edges.len());
+        let mut failed = None;
+        for eid in edges {
+            match self.withdraw_impl_edge(eid) {
+                Ok(()) => withdrawn.push(eid),
+                Err(e) => {
+                    failed = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let result = match failed {
+            Some(e) => Err(e),
+            None => op(self),
+        };
+
+        let mut failed = None;
+        for eid in withdrawn {
+            if let Err(e) = self.propagate_and_lift(eid) {
+                failed.get_or_insert(e);
+            }
+        }
+
+        match (result, failed) {
+            (Ok(_), Some(e)) => Err(e),
+            (result, _) => result,
+        }
+    }
+
+    /// Incremental: add a specified architecture edge to an analysed graph.
+    ///
+    /// Every propagated edge the new edge covers (same kind, endpoints inside the subtrees of
+    /// `from`/`to`) is lifted again. A Divergent one now lifts onto the new edge: it, all of
+    /// its implementation edges and the new edge become Convergent. One that was lifted to a
+    /// less specific ancestor edge moves over to the new, more specific edge.
+    /// Without any covered propagated edge the new edge is Absent.
+    pub fn add_arch_edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind) -> Result<EdgeId, GraphError> {
+        self.insert_arch_edge(from, to, kind, false)
+    }
+
+    /// Incremental: add an allowed architecture edge, i.e. a dependency that is permitted but
+    /// not required. Works like `add_arch_edge`, except that whatever lifts onto it is Allowed
+    /// instead of Divergent/Convergent, and without implementation it is AllowedAbsent.
+    pub fn add_allowed_edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind) -> Result<EdgeId, GraphError> {
+        self.insert_arch_edge(from, to, kind, true)
+    }
+
+    fn insert_arch_edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind, allowed: bool) -> Result<EdgeId, GraphError> {
+        for node in [from, to] {
+            let found = self.node_subgraph(node)?;
+            if found != SubgraphKind::Architecture {
+                return Err(GraphError::WrongSubgraph {
+                    node,
+                    expected: SubgraphKind::Architecture,
+                    found,
+                });
+            }
+        }
+
+        //unlift against the old architecture before the new edge can be matched
+        let kind = self.kinds.intern(&kind);
+        let first_of_kind = !self.kind_refs.contains_key(&kind);
+        let affected = self.propagated_edges_covered_by(from, to, kind);
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+
+        let id = self.add_edge(Edge {
+            id: 0, // overwritten by add_edge
+            from,
+            to,
+            kind,
+            subgraph: SubgraphKind::Architecture,
+            state: if allowed { EdgeState::AllowedAbsent } else { EdgeState::Absent },
+            counter: 0,
+            attrs: Default::default(),
+        })?;
+        if allowed {
+            self.allowed_edges.insert(id);
+        }
+
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        //implementation edges of subkinds now propagate with this kind
+        if first_of_kind {
+            self.regroup_propagated_edges(|g, k| g.is_subkind_id(kind, k))?;
+        }
+
+        Ok(id)
+    }
+
+    /// Incremental: remove a specified (or allowed) architecture edge from an analysed graph.
+    ///
+    /// Propagated edges that lifted onto it (and their implementation edges) become Divergent
+    /// again, unless another architecture edge (e.g. one between ancestors) still covers them.
+    pub fn remove_arch_edge(&mut self, edge_id: EdgeId) -> Result<(), GraphError> {
+        {
+            let e = self
+                .edges
+                .get(&edge_id)
+                .ok_or(GraphError::EdgeNotFound(edge_id))?;
+
+            if e.subgraph != SubgraphKind::Architecture {
+                return Err(GraphError::WrongSubgraph {
+                    node: e.from,
+                    expected: SubgraphKind::Architecture,
+                    found: e.subgraph,
+                });
+            }
         }
 
-        let from = e.from;
+        let affected = self.propagated_edges_lifted_to(edge_id);
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+
+        let kind = self.edges[&edge_id].kind;
+        let last_of_kind = self.kind_refs.get(&kind) == Some(&1);
+        self.detach_edge(edge_id);
+
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        if last_of_kind {
+            self.regroup_propagated_edges(|g, k| g.is_subkind_id(kind, k))?;
+        }
+
+        Ok(())
+    }
+
+    //propagated edges an architecture edge (from, to, kind) could lift: kind-or-subkind, source
+    //in from's subtree and target in to's subtree
+    fn propagated_edges_covered_by(&self, from: NodeId, to: NodeId, kind: KindId) -> Vec<EdgeId> {
+        let targets = self.descendants_or_self(to);
 
-        // Remove the edge object
-        self.edges.remove(&edge_id);
+        let mut out: Vec<EdgeId> = self
+            .descendants_or_self(from)
+            .into_iter()
+            .filter_map(|n| self.arch_out.get(&n))
+            .flatten()
+            .copied()
+            .filter(|eid| {
+                self.edges.get(eid).is_some_and(|e| {
+                    e.subgraph == SubgraphKind::Propagated && targets.contains(&e.to) && self.is_subkind_id(e.kind, kind)
+                })
+            })
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Incremental: remove a node together with everything hanging off it.
+    ///
+    /// The whole containment subtree goes (children before parents). For each removed node:
+    /// - implementation node: incident impl edges are removed decrementally and its own
+    ///   `maps_to` entry is dropped
+    /// - architecture node: impl nodes mapped onto it are unmapped incrementally and incident
+    ///   architecture edges are removed (re-lifting whatever was lifted onto them)
+    ///
+    /// Incident edges are found through the adjacency lists (`impl_out`/`impl_in`,
+    /// `arch_out`/`arch_in`), so the cost depends on the subtree, not on the size of the graph.
+    /// Finally the node is unlinked from its parent's `children` and from the adjacency lists.
+    pub fn remove_node(&mut self, node: NodeId) -> Result<(), GraphError> {
+        if !self.nodes.contains_key(&node) {
+            return Err(GraphError::NodeNotFound(node));
+        }
+
+        //post-order: deepest descendants first
+        let mut order = vec![node];
+        let mut i = 0;
+        while i < order.len() {
+            if let Some(n) = self.nodes.get(&order[i]) {
+                order.extend(n.children.iter().copied());
+            }
+            i += 1;
+        }
 
-        // Remove from adjacency
-        if let Some(v) = self.impl_out.get_mut(&from) {
-            v.retain(|&x| x != edge_id);
+        for &n in order.iter().rev() {
+            self.remove_single_node(n)?;
         }
 
-        // Recompute from scratch
-        self.clear_propagated_edges();
-        self.run_from_scratch();
+        Ok(())
+    }
+
+    fn remove_single_node(&mut self, node: NodeId) -> Result<(), GraphError> {
+        match self.node_subgraph(node)? {
+            SubgraphKind::Implementation => {
+                for eid in self.incident_impl_edges(node) {
+                    self.remove_impl_edge(eid)?;
+                }
+                self.maps_to.remove(&node);
+            }
+            SubgraphKind::Architecture | SubgraphKind::Propagated => {
+                let mut mapped: Vec<NodeId> = self
+                    .maps_to
+                    .iter()
+                    .filter(|&(_, &arch)| arch == node)
+                    .map(|(&impl_node, _)| impl_node)
+                    .collect();
+                mapped.sort_unstable();
+                for impl_node in mapped {
+                    self.remove_mapping_incremental(impl_node)?;
+                }
 
+                let mut incident: Vec<EdgeId> = [self.arch_out.get(&node), self.arch_in.get(&node)]
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .copied()
+                    .filter(|eid| self.edges.get(eid).is_some_and(|e| e.subgraph == SubgraphKind::Architecture))
+                    .collect();
+                incident.sort_unstable();
+                incident.dedup();
+                for eid in incident {
+                    self.remove_arch_edge(eid)?;
+                }
+
+                //through remove_forbidden_rule, so the edges a rule forbade are lifted again
+                let rules: Vec<RuleId> = self
+                    .forbidden_rules
+                    .iter()
+                    .filter(|(_, r)| r.from == Some(node) || r.to == Some(node))
+                    .map(|(&id, _)| id)
+                    .collect();
+                for rule_id in rules {
+                    self.remove_forbidden_rule(rule_id)?;
+                }
+                self.forget_layer_node(node);
+            }
+        }
+
+        self.detach_node(node);
         Ok(())
     }
+
+    /// Insert a prebuilt implementation edge and update the analysis incrementally.
+    ///
+    /// Kept for callers of the original API: despite the name nothing is recomputed, the edge
+    /// goes through the same delta path as `add_impl_edge`.
+    pub fn add_impl_edge_and_recompute(&mut self, edge: Edge) -> Result<EdgeId, GraphError> {
+        if edge.subgraph != SubgraphKind::Implementation {
+            return Err(GraphError::WrongSubgraph {
+                node: edge.from, // best available identifier here
+                expected: SubgraphKind::Implementation,
+                found: edge.subgraph,
+            });
+        }
+
+        self.insert_impl_edge(edge)
+    }
+
+    /// Remove an implementation edge and update the analysis decrementally.
+    ///
+    /// Kept for callers of the original API: same as `remove_impl_edge`, nothing is recomputed.
+    pub fn remove_impl_edge_and_recompute(&mut self, edge_id: EdgeId) -> Result<(), GraphError> {
+        self.remove_impl_edge(edge_id)
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
-    use std::ops::Sub;
+    use crate::core::forbidden::ForbiddenRule;
     use crate::core::graph::{Node, GraphError, ReflexionGraph, Edge};
     use crate::core::state::EdgeState;
-    use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind};
+    use crate::core::types::{NodeId, SubgraphKind, EdgeKind, KindId};
 
     fn mk_node(name: &str, subgraph: SubgraphKind) -> Node {
         Node::new(name, subgraph, None)
     }
 
-    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: EdgeKind) -> Edge {
+    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: KindId) -> Edge {
         Edge {
             id: 0,
             from,
@@ -83,6 +478,7 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         }
     }
 
@@ -93,32 +489,664 @@ mod tests {
         //architecture
         let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
         let service = g.add_node(mk_node("Service", SubgraphKind::Architecture)).unwrap();
-        let e_arch = g.add_edge(mk_edge(ui, service, SubgraphKind::Architecture, EdgeKind::depends_on())).unwrap();
+        let e_arch = g.add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::DEPENDS_ON)).unwrap();
 
         //implementation
         let login = g.add_node(mk_node("LoginPage", SubgraphKind::Implementation)).unwrap();
         let usersvc = g.add_node(mk_node("UserService", SubgraphKind::Implementation)).unwrap();
-        let e_impl_ok = g.add_edge(mk_edge(login, usersvc, SubgraphKind::Implementation, EdgeKind::depends_on())).unwrap();
+        let e_impl_ok = g.add_edge(mk_edge(login, usersvc, SubgraphKind::Implementation, KindId::DEPENDS_ON)).unwrap();
 
         //mapping
         g.set_mapping_overwrite(login, ui).unwrap();
         g.set_mapping_overwrite(usersvc, service).unwrap();
 
         //baseline
-        g.run_from_scratch();
+        g.run_from_scratch().unwrap();
         assert!(matches!(g.edges.get(&e_arch).unwrap().state, EdgeState::Convergent));
-        assert!(matches!(g.edges.get(&e_impl_ok).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&e_impl_ok).unwrap().state, EdgeState::Convergent));
 
         //add a divergent impl edge (reverse direction)
-        let new_div = mk_edge(usersvc, login, SubgraphKind::Implementation, EdgeKind::depends_on());
+        let prop_ok = g.propagated_edge_of(e_impl_ok).unwrap();
+        let new_div = mk_edge(usersvc, login, SubgraphKind::Implementation, KindId::DEPENDS_ON);
         let e_impl_div = g.add_impl_edge_and_recompute(new_div).unwrap();
 
         assert!(matches!(g.edges.get(&e_impl_div).unwrap().state, EdgeState::Divergent));
+        //delta path: the existing propagated edge was not rebuilt
+        assert_eq!(g.propagated_edge_of(e_impl_ok), Some(prop_ok));
 
         //remove it and ensure we’re back to baseline
         g.remove_impl_edge_and_recompute(e_impl_div).unwrap();
+        assert!(g.edge(e_impl_div).is_none());
+        assert_eq!(g.propagated_edge_of(e_impl_ok), Some(prop_ok));
 
         assert!(matches!(g.edges.get(&e_arch).unwrap().state, EdgeState::Convergent));
-        assert!(matches!(g.edges.get(&e_impl_ok).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&e_impl_ok).unwrap().state, EdgeState::Convergent));
+    }
+
+    //order-independent view of the analysis result: edge ids differ between an incremental
+    //and a from-scratch run, so compare by (subgraph, endpoints, kind, state, counter)
+    fn classification(g: &ReflexionGraph) -> Vec<String> {
+        let mut out: Vec<String> = g
+            .edges
+            .values()
+            .map(|e| format!("{:?} {}->{} {} {:?} {}", e.subgraph, e.from, e.to, g.resolve_kind(e.kind), e.state, e.counter))
+            .collect();
+        out.sort();
+        out
+    }
+
+    struct Model {
+        g: ReflexionGraph,
+        ui: NodeId,
+        service: NodeId,
+        db: NodeId,
+        login: NodeId,
+        usersvc: NodeId,
+        repo: NodeId,
+        helper: NodeId,
+    }
+
+    //UI -> Service -> DB specified, helper is never mapped
+    fn layered_model() -> Model {
+        let mut g = ReflexionGraph::new();
+
+        let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
+        let service = g.add_node(mk_node("Service", SubgraphKind::Architecture)).unwrap();
+        let db = g.add_node(mk_node("DB", SubgraphKind::Architecture)).unwrap();
+        g.add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+        g.add_edge(mk_edge(service, db, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+
+        let login = g.add_node(mk_node("LoginPage", SubgraphKind::Implementation)).unwrap();
+        let usersvc = g.add_node(mk_node("UserService", SubgraphKind::Implementation)).unwrap();
+        let repo = g.add_node(mk_node("UserRepo", SubgraphKind::Implementation)).unwrap();
+        let helper = g.add_node(mk_node("Helper", SubgraphKind::Implementation)).unwrap();
+
+        g.set_mapping(login, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+
+        Model { g, ui, service, db, login, usersvc, repo, helper }
+    }
+
+    #[test]
+    fn incremental_add_matches_from_scratch() {
+        let mut inc = layered_model();
+        let mut full = layered_model();
+
+        //shared baseline
+        inc.g.add_edge(mk_edge(inc.login, inc.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        inc.g.run_from_scratch().unwrap();
+
+        //incremental inserts: convergent (Absent -> Convergent), duplicate, divergent, unmapped
+        inc.g.add_impl_edge(inc.usersvc, inc.repo, EdgeKind::calls()).unwrap();
+        inc.g.add_impl_edge(inc.login, inc.usersvc, EdgeKind::calls()).unwrap();
+        inc.g.add_impl_edge(inc.login, inc.repo, EdgeKind::calls()).unwrap();
+        inc.g.add_impl_edge(inc.helper, inc.login, EdgeKind::calls()).unwrap();
+
+        //same edges, one full run
+        for (from, to) in [
+            (full.login, full.usersvc),
+            (full.usersvc, full.repo),
+            (full.login, full.usersvc),
+            (full.login, full.repo),
+            (full.helper, full.login),
+        ] {
+            full.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        full.g.run_from_scratch().unwrap();
+
+        assert_eq!(classification(&inc.g), classification(&full.g));
+    }
+
+    #[test]
+    fn incremental_add_only_touches_affected_edges() {
+        let mut m = layered_model();
+        let e_ok = m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+
+        let props_before = m.g.edges.values().filter(|e| e.subgraph == SubgraphKind::Propagated).count();
+
+        let e_new = m.g.add_impl_edge(m.usersvc, m.repo, EdgeKind::calls()).unwrap();
+
+        //exactly one new propagated edge (Service -> DB), previous one untouched
+        let props_after = m.g.edges.values().filter(|e| e.subgraph == SubgraphKind::Propagated).count();
+        assert_eq!(props_after, props_before + 1);
+        assert!(matches!(m.g.edges.get(&e_ok).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(m.g.edges.get(&e_new).unwrap().state, EdgeState::Convergent));
+
+        let svc_db = m
+            .g
+            .edges
+            .values()
+            .find(|e| e.subgraph == SubgraphKind::Architecture && e.from == m.service && e.to == m.db)
+            .unwrap();
+        assert!(matches!(svc_db.state, EdgeState::Convergent));
+        assert_eq!(svc_db.counter, 1);
+
+        let ui_svc = m
+            .g
+            .edges
+            .values()
+            .find(|e| e.subgraph == SubgraphKind::Architecture && e.from == m.ui && e.to == m.service)
+            .unwrap();
+        assert_eq!(ui_svc.counter, 1);
+    }
+
+    #[test]
+    fn incremental_add_rejects_non_implementation_endpoints() {
+        let mut m = layered_model();
+        m.g.run_from_scratch().unwrap();
+
+        let err = m.g.add_impl_edge(m.ui, m.login, EdgeKind::calls()).unwrap_err();
+        assert_eq!(
+            err,
+            GraphError::WrongSubgraph {
+                node: m.ui,
+                expected: SubgraphKind::Implementation,
+                found: SubgraphKind::Architecture,
+            }
+        );
+    }
+
+    #[test]
+    fn incremental_remove_matches_from_scratch() {
+        let mut inc = layered_model();
+        let mut full = layered_model();
+
+        let mut removed = vec![];
+        for (from, to, drop) in [
+            (inc.login, inc.usersvc, false),
+            (inc.login, inc.usersvc, true),
+            (inc.usersvc, inc.repo, true),
+            (inc.login, inc.repo, true),
+            (inc.helper, inc.login, true),
+        ] {
+            let eid = inc.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            if drop {
+                removed.push(eid);
+            }
+        }
+        inc.g.run_from_scratch().unwrap();
+
+        for eid in removed {
+            inc.g.remove_impl_edge(eid).unwrap();
+        }
+
+        full.g.add_edge(mk_edge(full.login, full.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        full.g.run_from_scratch().unwrap();
+
+        assert_eq!(classification(&inc.g), classification(&full.g));
+    }
+
+    #[test]
+    fn incremental_remove_drops_propagated_edge_and_restores_absent() {
+        let mut m = layered_model();
+        let e1 = m.g.add_edge(mk_edge(m.usersvc, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        let e2 = m.g.add_edge(mk_edge(m.usersvc, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+
+        let arch_id = m
+            .g
+            .lift_exact(m.service, m.db, &EdgeKind::calls())
+            .unwrap()
+            .unwrap();
+        let prop_id = *m
+            .g
+            .propagation_table
+            .iter()
+            .find(|(_, impls)| impls.contains(&e1))
+            .unwrap()
+            .0;
+
+        //first removal: both counters go down, nothing disappears
+        m.g.remove_impl_edge(e1).unwrap();
+        assert!(!m.g.edges.contains_key(&e1));
+        assert_eq!(m.g.edges.get(&prop_id).unwrap().counter, 1);
+        assert_eq!(m.g.edges.get(&arch_id).unwrap().counter, 1);
+        assert!(matches!(m.g.edges.get(&arch_id).unwrap().state, EdgeState::Convergent));
+
+        //last supporting edge gone: propagated edge deleted, arch edge back to Absent
+        m.g.remove_impl_edge(e2).unwrap();
+        assert!(!m.g.edges.contains_key(&prop_id));
+        assert!(!m.g.propagation_table.contains_key(&prop_id));
+        assert!(!m.g.arch_out.get(&m.service).unwrap().contains(&prop_id));
+        let ae = m.g.edges.get(&arch_id).unwrap();
+        assert_eq!(ae.counter, 0);
+        assert!(matches!(ae.state, EdgeState::Absent));
+    }
+
+    #[test]
+    fn incremental_updates_never_reuse_propagated_edge_ids() {
+        let mut m = layered_model();
+        m.g.run_from_scratch().unwrap();
+        let e_ui_svc = m.g.add_impl_edge(m.login, m.usersvc, EdgeKind::calls()).unwrap();
+        let old_prop = m.g.propagated_edge_of(e_ui_svc).unwrap();
+
+        m.g.remove_impl_edge(e_ui_svc).unwrap();
+        let e_svc_db = m.g.add_impl_edge(m.usersvc, m.repo, EdgeKind::calls()).unwrap();
+
+        //an id kept from before must not start to describe Service -> DB
+        assert_ne!(m.g.propagated_edge_of(e_svc_db), Some(old_prop));
+        assert_eq!(m.g.explain_edge(old_prop).unwrap_err(), GraphError::EdgeNotFound(old_prop));
+    }
+
+    #[test]
+    fn incremental_remove_rejects_architecture_edge() {
+        let mut m = layered_model();
+        m.g.run_from_scratch().unwrap();
+        let arch_id = m.g.lift_exact(m.ui, m.service, &EdgeKind::calls()).unwrap().unwrap();
+
+        let err = m.g.remove_impl_edge(arch_id).unwrap_err();
+        assert!(matches!(err, GraphError::WrongSubgraph { .. }));
+        assert_eq!(m.g.remove_impl_edge(999).unwrap_err(), GraphError::EdgeNotFound(999));
+    }
+
+    #[test]
+    fn incremental_remapping_matches_from_scratch() {
+        let mut inc = layered_model();
+        for (from, to) in [
+            (inc.login, inc.usersvc),
+            (inc.usersvc, inc.repo),
+            (inc.login, inc.repo),
+            (inc.helper, inc.repo),
+            (inc.repo, inc.repo),
+        ] {
+            inc.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        inc.g.run_from_scratch().unwrap();
+
+        //move UserService into DB, map Helper, unmap LoginPage
+        inc.g.set_mapping_overwrite_incremental(inc.usersvc, inc.db).unwrap();
+        inc.g.set_mapping_incremental(inc.helper, inc.ui).unwrap();
+        inc.g.remove_mapping_incremental(inc.login).unwrap();
+
+        //same final mapping, analysed in one go
+        let mut full = layered_model();
+        for (from, to) in [
+            (full.login, full.usersvc),
+            (full.usersvc, full.repo),
+            (full.login, full.repo),
+            (full.helper, full.repo),
+            (full.repo, full.repo),
+        ] {
+            full.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        full.g.set_mapping_overwrite(full.usersvc, full.db).unwrap();
+        full.g.set_mapping(full.helper, full.ui).unwrap();
+        full.g.remove_mapping(full.login).unwrap();
+        full.g.run_from_scratch().unwrap();
+
+        assert_eq!(classification(&inc.g), classification(&full.g));
+    }
+
+    #[test]
+    fn incremental_set_mapping_error_leaves_analysis_unchanged() {
+        let mut m = layered_model();
+        m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+        let before = classification(&m.g);
+
+        let err = m.g.set_mapping_incremental(m.login, m.db).unwrap_err();
+        assert_eq!(
+            err,
+            GraphError::MappingAlreadyExists { impl_node: m.login, old_arch: m.ui, new_arch: m.db }
+        );
+        assert_eq!(classification(&m.g), before);
+    }
+
+    #[test]
+    fn failed_withdrawal_re_propagates_what_was_withdrawn() {
+        let mut m = layered_model();
+        let e = m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+        let before = classification(&m.g);
+
+        //e is withdrawn, then 999 fails: the mapping change never runs and e comes back
+        let err = m.g.with_edges_withdrawn(vec![e, 999], |g| g.remove_mapping(m.login)).unwrap_err();
+        assert_eq!(err, GraphError::EdgeNotFound(999));
+        assert_eq!(m.g.get_arch_node(m.login).unwrap(), Some(m.ui));
+        assert_eq!(classification(&m.g), before);
+    }
+
+    #[test]
+    fn incremental_clear_mappings_marks_everything_unmapped() {
+        let mut m = layered_model();
+        let e = m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+
+        m.g.clear_mappings_incremental().unwrap();
+
+        assert_eq!(m.g.mapping_len(), 0);
+        assert!(matches!(m.g.edges.get(&e).unwrap().state, EdgeState::Unmapped));
+        assert!(!m.g.edges.values().any(|e| e.subgraph == SubgraphKind::Propagated));
+        assert!(m
+            .g
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Architecture)
+            .all(|e| matches!(e.state, EdgeState::Absent) && e.counter == 0));
+    }
+
+    #[test]
+    fn incremental_add_arch_edge_turns_divergence_into_convergence() {
+        let mut m = layered_model();
+        let e1 = m.g.add_edge(mk_edge(m.login, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        let e2 = m.g.add_edge(mk_edge(m.login, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+        assert!(matches!(m.g.edges.get(&e1).unwrap().state, EdgeState::Divergent));
+
+        let arch_id = m.g.add_arch_edge(m.ui, m.db, EdgeKind::calls()).unwrap();
+
+        let ae = m.g.edges.get(&arch_id).unwrap();
+        assert!(matches!(ae.state, EdgeState::Convergent));
+        assert_eq!(ae.counter, 2);
+        assert!(matches!(m.g.edges.get(&e1).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(m.g.edges.get(&e2).unwrap().state, EdgeState::Convergent));
+        assert!(m
+            .g
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Propagated)
+            .all(|e| matches!(e.state, EdgeState::Convergent)));
+
+        //an arch edge nobody implements is simply Absent
+        let lonely = m.g.add_arch_edge(m.db, m.ui, EdgeKind::calls()).unwrap();
+        assert!(matches!(m.g.edges.get(&lonely).unwrap().state, EdgeState::Absent));
+    }
+
+    #[test]
+    fn incremental_arch_edge_changes_match_from_scratch() {
+        let mut inc = layered_model();
+        for (from, to) in [(inc.login, inc.usersvc), (inc.login, inc.repo), (inc.usersvc, inc.repo)] {
+            inc.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        inc.g.run_from_scratch().unwrap();
+
+        let ui_svc = inc.g.lift_exact(inc.ui, inc.service, &EdgeKind::calls()).unwrap().unwrap();
+        inc.g.remove_arch_edge(ui_svc).unwrap();
+        inc.g.add_arch_edge(inc.ui, inc.db, EdgeKind::calls()).unwrap();
+
+        let mut full = ReflexionGraph::new();
+        let ui = full.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
+        let service = full.add_node(mk_node("Service", SubgraphKind::Architecture)).unwrap();
+        let db = full.add_node(mk_node("DB", SubgraphKind::Architecture)).unwrap();
+        full.add_edge(mk_edge(service, db, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+        full.add_edge(mk_edge(ui, db, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+        let login = full.add_node(mk_node("LoginPage", SubgraphKind::Implementation)).unwrap();
+        let usersvc = full.add_node(mk_node("UserService", SubgraphKind::Implementation)).unwrap();
+        let repo = full.add_node(mk_node("UserRepo", SubgraphKind::Implementation)).unwrap();
+        full.add_node(mk_node("Helper", SubgraphKind::Implementation)).unwrap();
+        full.set_mapping(login, ui).unwrap();
+        full.set_mapping(usersvc, service).unwrap();
+        full.set_mapping(repo, db).unwrap();
+        for (from, to) in [(login, usersvc), (login, repo), (usersvc, repo)] {
+            full.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        full.run_from_scratch().unwrap();
+
+        assert_eq!(classification(&inc.g), classification(&full));
+    }
+
+    #[test]
+    fn incremental_remove_arch_edge_rejects_non_architecture_edges() {
+        let mut m = layered_model();
+        let e = m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+
+        assert!(matches!(m.g.remove_arch_edge(e).unwrap_err(), GraphError::WrongSubgraph { .. }));
+        assert!(matches!(
+            m.g.add_arch_edge(m.login, m.ui, EdgeKind::calls()).unwrap_err(),
+            GraphError::WrongSubgraph { .. }
+        ));
+    }
+
+    #[test]
+    fn remove_impl_node_matches_from_scratch_without_it() {
+        let mut inc = layered_model();
+        for (from, to) in [
+            (inc.login, inc.usersvc),
+            (inc.usersvc, inc.repo),
+            (inc.login, inc.repo),
+            (inc.helper, inc.usersvc),
+        ] {
+            inc.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        inc.g.run_from_scratch().unwrap();
+
+        inc.g.remove_node(inc.repo).unwrap();
+
+        let mut full = layered_model();
+        for (from, to) in [(full.login, full.usersvc), (full.helper, full.usersvc)] {
+            full.g.add_edge(mk_edge(from, to, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        }
+        full.g.remove_mapping(full.repo).unwrap();
+        full.g.detach_node(full.repo);
+        full.g.run_from_scratch().unwrap();
+
+        assert_eq!(classification(&inc.g), classification(&full.g));
+        assert!(!inc.g.nodes.contains_key(&inc.repo));
+        assert!(!inc.g.maps_to.contains_key(&inc.repo));
+        assert!(!inc.g.impl_out.contains_key(&inc.repo));
+        assert!(!inc.g.impl_in.contains_key(&inc.repo));
+        assert!(inc.g.impl_out[&inc.usersvc].is_empty()); //usersvc -> repo went with repo
+    }
+
+    #[test]
+    fn remove_arch_node_unmaps_and_drops_arch_edges() {
+        let mut m = layered_model();
+        let e_svc_db = m.g.add_edge(mk_edge(m.usersvc, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        let e_ui_svc = m.g.add_edge(mk_edge(m.login, m.usersvc, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+
+        m.g.remove_node(m.db).unwrap();
+
+        assert!(!m.g.nodes.contains_key(&m.db));
+        assert_eq!(m.g.get_arch_node(m.repo).unwrap(), None);
+        assert!(matches!(m.g.edges.get(&e_svc_db).unwrap().state, EdgeState::Unmapped));
+        assert!(matches!(m.g.edges.get(&e_ui_svc).unwrap().state, EdgeState::Convergent));
+        assert!(!m.g.edges.values().any(|e| e.from == m.db || e.to == m.db));
+        assert!(m.g.arch_out.get(&m.service).unwrap().iter().all(|eid| m.g.edges.contains_key(eid)));
+        assert!(!m.g.arch_in.contains_key(&m.db));
+        assert!(m.g.arch_in.get(&m.service).unwrap().iter().all(|eid| m.g.edges.contains_key(eid)));
+    }
+
+    #[test]
+    fn remove_arch_node_drops_the_forbidden_rules_naming_it() {
+        let mut m = layered_model();
+        let e_ui_db = m.g.add_edge(mk_edge(m.login, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        let e_svc_ui = m.g.add_edge(mk_edge(m.usersvc, m.login, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+        m.g.add_forbidden_rule(ForbiddenRule::new(m.ui, m.db)).unwrap();
+        let kept = m.g.add_forbidden_rule(ForbiddenRule::any_to(m.ui)).unwrap();
+        assert!(matches!(m.g.edges.get(&e_ui_db).unwrap().state, EdgeState::Forbidden));
+
+        m.g.remove_node(m.db).unwrap();
+
+        assert_eq!(m.g.forbidden_rules().map(|(id, _)| id).collect::<Vec<_>>(), vec![kept]);
+        assert!(matches!(m.g.edges.get(&e_ui_db).unwrap().state, EdgeState::Unmapped));
+        assert!(matches!(m.g.edges.get(&e_svc_ui).unwrap().state, EdgeState::Forbidden));
+        assert!(m.g.forbidden_by.values().all(|&r| r == kept));
+
+        //the graph can still be saved and loaded again
+        let mut saved = Vec::new();
+        m.g.write_snapshot(&mut saved).unwrap();
+        let loaded = ReflexionGraph::read_snapshot(saved.as_slice()).unwrap();
+        assert_eq!(classification(&loaded), classification(&m.g));
+    }
+
+    #[test]
+    fn remove_node_takes_the_subtree_and_unlinks_parent() {
+        let mut g = ReflexionGraph::new();
+        let arch = g.add_node(mk_node("Arch", SubgraphKind::Architecture)).unwrap();
+        let pkg = g.add_node(mk_node("pkg", SubgraphKind::Implementation)).unwrap();
+        let class = g.add_node(Node::new("Class", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let method = g.add_node(Node::new("method", SubgraphKind::Implementation, Some(class))).unwrap();
+        let other = g.add_node(mk_node("other", SubgraphKind::Implementation)).unwrap();
+        g.set_mapping(method, arch).unwrap();
+        g.set_mapping(other, arch).unwrap();
+        let e = g.add_edge(mk_edge(other, method, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        g.run_from_scratch().unwrap();
+
+        g.remove_node(class).unwrap();
+
+        assert!(!g.nodes.contains_key(&class));
+        assert!(!g.nodes.contains_key(&method));
+        assert!(g.nodes.get(&pkg).unwrap().children.is_empty());
+        assert!(!g.edges.contains_key(&e));
+        assert!(!g.edges.values().any(|e| e.subgraph == SubgraphKind::Propagated));
+        assert_eq!(g.mapping_len(), 1);
+
+        assert_eq!(g.remove_node(class).unwrap_err(), GraphError::NodeNotFound(class));
+    }
+
+    #[test]
+    fn incremental_subtree_mapping_matches_from_scratch() {
+        fn model() -> (ReflexionGraph, NodeId, NodeId, NodeId) {
+            let mut g = ReflexionGraph::new();
+            let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
+            let db = g.add_node(mk_node("DB", SubgraphKind::Architecture)).unwrap();
+            g.add_edge(mk_edge(ui, db, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+
+            let pkg = g.add_node(mk_node("ui_pkg", SubgraphKind::Implementation)).unwrap();
+            let class = g.add_node(Node::new("View", SubgraphKind::Implementation, Some(pkg))).unwrap();
+            let method = g.add_node(Node::new("render", SubgraphKind::Implementation, Some(class))).unwrap();
+            let store = g.add_node(mk_node("store", SubgraphKind::Implementation)).unwrap();
+            g.set_mapping(store, db).unwrap();
+
+            g.add_edge(mk_edge(method, store, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            g.add_edge(mk_edge(class, store, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            g.add_edge(mk_edge(store, method, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            (g, ui, pkg, class)
+        }
+
+        let (mut inc, ui, pkg, class) = model();
+        inc.run_from_scratch().unwrap();
+        assert!(inc.edges.values().all(|e| e.subgraph != SubgraphKind::Implementation
+            || matches!(e.state, EdgeState::Unmapped)));
+
+        //mapping the package covers the class and its method
+        inc.set_mapping_incremental(pkg, ui).unwrap();
+        let (mut full, _, _, _) = model();
+        full.set_mapping(pkg, ui).unwrap();
+        full.run_from_scratch().unwrap();
+        assert_eq!(classification(&inc), classification(&full));
+
+        //an explicit mapping on the class overrides the package, then unmapping the package
+        //only affects what still inherits from it
+        inc.set_mapping_incremental(class, ui).unwrap();
+        inc.remove_mapping_incremental(pkg).unwrap();
+        let (mut full, _, _, _) = model();
+        full.set_mapping(class, ui).unwrap();
+        full.run_from_scratch().unwrap();
+        assert_eq!(classification(&inc), classification(&full));
+
+        //unmapping the class leaves the whole subtree unmapped again
+        inc.remove_mapping_incremental(class).unwrap();
+        let (mut full, _, _, _) = model();
+        full.run_from_scratch().unwrap();
+        assert_eq!(classification(&inc), classification(&full));
+    }
+
+    #[test]
+    fn incremental_arch_edges_relift_across_the_hierarchy() {
+        //Frontend > Views, Backend > Store; Frontend -> Backend specified
+        fn model(specific: bool) -> (ReflexionGraph, NodeId, NodeId, NodeId) {
+            let mut g = ReflexionGraph::new();
+            let frontend = g.add_node(mk_node("Frontend", SubgraphKind::Architecture)).unwrap();
+            let views = g.add_node(Node::new("Views", SubgraphKind::Architecture, Some(frontend))).unwrap();
+            let backend = g.add_node(mk_node("Backend", SubgraphKind::Architecture)).unwrap();
+            let store = g.add_node(Node::new("Store", SubgraphKind::Architecture, Some(backend))).unwrap();
+            let fb = g.add_edge(mk_edge(frontend, backend, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+            if specific {
+                g.add_edge(mk_edge(views, store, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+            }
+
+            let page = g.add_node(mk_node("Page", SubgraphKind::Implementation)).unwrap();
+            let repo = g.add_node(mk_node("Repo", SubgraphKind::Implementation)).unwrap();
+            g.set_mapping(page, views).unwrap();
+            g.set_mapping(repo, store).unwrap();
+            g.add_edge(mk_edge(page, repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            g.add_edge(mk_edge(page, repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+            (g, views, store, fb)
+        }
+
+        let (mut inc, views, store, fb) = model(false);
+        inc.run_from_scratch().unwrap();
+        assert_eq!(inc.edges.get(&fb).unwrap().counter, 2);
+
+        //the more specific edge takes over, the ancestor edge becomes Absent
+        let vs = inc.add_arch_edge(views, store, EdgeKind::calls()).unwrap();
+        let (mut full, _, _, _) = model(true);
+        full.run_from_scratch().unwrap();
+        assert_eq!(classification(&inc), classification(&full));
+        assert!(matches!(inc.edges.get(&fb).unwrap().state, EdgeState::Absent));
+        assert_eq!(inc.edges.get(&vs).unwrap().counter, 2);
+
+        //removing it falls back to the ancestor edge
+        inc.remove_arch_edge(vs).unwrap();
+        let (mut full, _, _, _) = model(false);
+        full.run_from_scratch().unwrap();
+        assert_eq!(classification(&inc), classification(&full));
+        assert!(matches!(inc.edges.get(&fb).unwrap().state, EdgeState::Convergent));
+    }
+
+    #[test]
+    fn incremental_allowed_edges_never_become_violations() {
+        let mut m = layered_model();
+        let e = m.g.add_edge(mk_edge(m.login, m.repo, SubgraphKind::Implementation, KindId::CALLS)).unwrap();
+        m.g.run_from_scratch().unwrap();
+        assert!(matches!(m.g.edges.get(&e).unwrap().state, EdgeState::Divergent));
+
+        //UI -> DB becomes an optional dependency: the divergence turns into Allowed
+        let allowed = m.g.add_allowed_edge(m.ui, m.db, EdgeKind::calls()).unwrap();
+        assert!(m.g.is_allowed_edge(allowed));
+        assert!(matches!(m.g.edges.get(&e).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(m.g.edges.get(&allowed).unwrap().state, EdgeState::Allowed));
+
+        //dropping the only implementation: AllowedAbsent, not Absent
+        m.g.remove_impl_edge(e).unwrap();
+        assert!(matches!(m.g.edges.get(&allowed).unwrap().state, EdgeState::AllowedAbsent));
+        assert!(!m.g.edges.get(&allowed).unwrap().state.is_violation());
+
+        //removing the allowed edge itself cleans up the marker
+        m.g.remove_arch_edge(allowed).unwrap();
+        assert!(!m.g.is_allowed_edge(allowed));
+    }
+
+    //impl_to_prop must mirror propagation_table exactly
+    fn assert_reverse_index_consistent(g: &ReflexionGraph) {
+        let forward: usize = g.propagation_table.values().map(|s| s.len()).sum();
+        assert_eq!(forward, g.impl_to_prop.len());
+        for (&ie, &prop) in &g.impl_to_prop {
+            assert!(g.propagation_table[&prop].contains(&ie));
+        }
+    }
+
+    #[test]
+    fn reverse_index_follows_every_update() {
+        let mut m = layered_model();
+        let e1 = m.g.add_impl_edge(m.login, m.usersvc, EdgeKind::calls()).unwrap();
+        let e2 = m.g.add_impl_edge(m.login, m.usersvc, EdgeKind::calls()).unwrap();
+        let e3 = m.g.add_impl_edge(m.usersvc, m.repo, EdgeKind::calls()).unwrap();
+        m.g.add_impl_edge(m.helper, m.repo, EdgeKind::calls()).unwrap();
+        assert_reverse_index_consistent(&m.g);
+        assert_eq!(m.g.propagated_edge_of(e1), m.g.propagated_edge_of(e2));
+
+        m.g.remove_impl_edge(e1).unwrap();
+        assert_eq!(m.g.propagated_edge_of(e1), None);
+        assert_reverse_index_consistent(&m.g);
+
+        m.g.set_mapping_overwrite_incremental(m.repo, m.ui).unwrap();
+        m.g.set_mapping_incremental(m.helper, m.service).unwrap();
+        assert_reverse_index_consistent(&m.g);
+
+        m.g.remove_node(m.usersvc).unwrap();
+        assert_eq!(m.g.propagated_edge_of(e2), None);
+        assert_eq!(m.g.propagated_edge_of(e3), None);
+        assert_reverse_index_consistent(&m.g);
+
+        m.g.run_from_scratch().unwrap();
+        assert_reverse_index_consistent(&m.g);
+        assert_eq!(m.g.impl_to_prop.len(), 1);
     }
 }
@@ -0,0 +1,159 @@
+// dense id-indexed storage for nodes, edges and per-node adjacency
+//
+// Node and edge ids are handed out sequentially by fresh_node_id / fresh_edge_id, so a Vec
+// indexed by id replaces a HashMap: lookups are a bounds check instead of a hash, and
+// iteration walks contiguous memory in id order (which also makes every iteration over the
+// graph deterministic). Removed entries leave a tombstone (None) behind. Ids are only reused
+// within a full run, which gives the propagated edges it drops to the ones it builds (see
+// add_edge) and so keeps repeated full runs from piling up tombstones; otherwise an id is
+// never handed out twice.
+use std::ops::Index;
+
+#[derive(Debug, Clone)]
+pub struct DenseMap<V> {
+    slots: Vec<Option<V>>, //indexed by id, None = never used or removed
+    len: usize,
+}
+
+impl<V> Default for DenseMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> DenseMap<V> {
+    pub fn new() -> Self {
+        Self { slots: Vec::new(), len: 0 }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { slots: Vec::with_capacity(capacity), len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    //live entries plus tombstones
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.slots.reserve(additional);
+    }
+
+    pub fn get(&self, id: &u32) -> Option<&V> {
+        self.slots.get(*id as usize)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: &u32) -> Option<&mut V> {
+        self.slots.get_mut(*id as usize)?.as_mut()
+    }
+
+    pub fn contains_key(&self, id: &u32) -> bool {
+        self.get(id).is_some()
+    }
+
+    //returns the previous value, like HashMap::insert
+    pub fn insert(&mut self, id: u32, value: V) -> Option<V> {
+        let i = id as usize;
+        if i >= self.slots.len() {
+            self.slots.resize_with(i + 1, || None);
+        }
+        let old = self.slots[i].replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove(&mut self, id: &u32) -> Option<V> {
+        let old = self.slots.get_mut(*id as usize)?.take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    //stands in for entry(id).or_default()
+    pub fn get_or_insert_default(&mut self, id: u32) -> &mut V
+    where
+        V: Default,
+    {
+        if !self.contains_key(&id) {
+            self.insert(id, V::default());
+        }
+        self.slots[id as usize].as_mut().expect("just inserted")
+    }
+
+    //keeps the allocation, like HashMap::clear
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    //everything below iterates in id order and skips tombstones
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &V)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(i, v)| Some((i as u32, v.as_ref()?)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut V)> + '_ {
+        self.slots.iter_mut().enumerate().filter_map(|(i, v)| Some((i as u32, v.as_mut()?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = u32> + '_ {
+        self.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.slots.iter().filter_map(Option::as_ref)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.slots.iter_mut().filter_map(Option::as_mut)
+    }
+}
+
+impl<V> Index<&u32> for DenseMap<V> {
+    type Output = V;
+
+    fn index(&self, id: &u32) -> &V {
+        self.get(id).expect("no entry for id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DenseMap;
+
+    #[test]
+    fn insert_remove_and_iterate_in_id_order() {
+        let mut m: DenseMap<&str> = DenseMap::new();
+        assert_eq!(m.insert(3, "c"), None);
+        assert_eq!(m.insert(1, "a"), None);
+        assert_eq!(m.insert(2, "b"), None);
+        assert_eq!(m.insert(2, "B"), Some("b"));
+        assert_eq!(m.len(), 3);
+        assert_eq!(m[&2], "B");
+
+        assert_eq!(m.remove(&2), Some("B"));
+        assert_eq!(m.remove(&2), None);
+        assert_eq!(m.remove(&99), None);
+        assert!(!m.contains_key(&2));
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.capacity(), 4); //slot 0 and the tombstone at 2 stay
+        assert_eq!(m.iter().collect::<Vec<_>>(), vec![(1, &"a"), (3, &"c")]);
+
+        let mut adj: DenseMap<Vec<u32>> = DenseMap::new();
+        adj.get_or_insert_default(5).push(7);
+        adj.get_or_insert_default(5).push(8);
+        assert_eq!(adj.get(&5), Some(&vec![7, 8]));
+        adj.clear();
+        assert!(adj.is_empty() && adj.get(&5).is_none());
+    }
+}
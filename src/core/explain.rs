@@ -0,0 +1,263 @@
+// provenance: which implementation edges are behind an architecture/propagated edge
+//
+// Reads propagation_table (propagated edge -> impl edges), lifted_to (propagated edge ->
+// architecture edge) and their reverse indexes impl_to_prop and lifted_from; nothing is
+// recomputed, so the answer reflects the current analysis.
+use std::fmt::Write;
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::types::{Attributes, EdgeId, EdgeKind, NodeId, RuleId, SubgraphKind};
+
+//one end of an implementation edge and how it got its architecture component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointInfo {
+    pub node: NodeId,
+    pub path: String,
+    pub arch_node: Option<NodeId>,
+    pub arch_path: Option<String>,
+    pub mapped_via: Option<NodeId>, //the node whose maps_to entry applies (itself or an ancestor)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplEdgeInfo {
+    pub edge: EdgeId,
+    pub kind: EdgeKind,
+    pub state: EdgeState,
+    pub from: EndpointInfo,
+    pub to: EndpointInfo,
+    pub attrs: Attributes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub edge: EdgeId,
+    pub subgraph: SubgraphKind,
+    pub state: EdgeState,
+    pub propagated: Vec<EdgeId>, //propagated edges involved (the edge itself if propagated)
+    pub lifted_to: Option<EdgeId>, //for a propagated edge: the architecture edge covering it
+    pub forbidden_by: Option<RuleId>, //for a propagated edge: the rule forbidding it
+    pub impl_edges: Vec<ImplEdgeInfo>,
+    pub text: String, //human-readable summary, one implementation edge per line
+}
+
+impl ReflexionGraph {
+    /// Explain the classification of an edge. An implementation edge is explained through the
+    /// propagated edge it contributes to; `edge`, `subgraph` and `state` still describe the
+    /// implementation edge itself and `impl_edges` holds only it.
+    pub fn explain_edge(&self, edge_id: EdgeId) -> Result<Explanation, GraphError> {
+        let e = self.edges.get(&edge_id).ok_or(GraphError::EdgeNotFound(edge_id))?;
+
+        let (propagated, lifted_to, forbidden_by) = match e.subgraph {
+            SubgraphKind::Architecture => (self.propagated_edges_lifted_to(edge_id), None, None),
+            SubgraphKind::Propagated => (
+                vec![edge_id],
+                self.lifted_to.get(&edge_id).copied(),
+                self.forbidden_by.get(&edge_id).copied(),
+            ),
+            SubgraphKind::Implementation => match self.propagated_edge_of(edge_id) {
+                Some(prop) => (
+                    vec![prop],
+                    self.lifted_to.get(&prop).copied(),
+                    self.forbidden_by.get(&prop).copied(),
+                ),
+                None => (Vec::new(), None, None),
+            },
+        };
+
+        let mut impl_ids: Vec<EdgeId> = if e.subgraph == SubgraphKind::Implementation {
+            vec![edge_id]
+        } else {
+            propagated
+                .iter()
+                .filter_map(|p| self.propagation_table.get(p))
+                .flatten()
+                .copied()
+                .collect()
+        };
+        impl_ids.sort_unstable();
+        let impl_edges: Vec<ImplEdgeInfo> = impl_ids.into_iter().filter_map(|i| self.impl_edge_info(i)).collect();
+
+        let text = self.explanation_text(e, &propagated, lifted_to, forbidden_by, &impl_edges);
+        Ok(Explanation {
+            edge: edge_id,
+            subgraph: e.subgraph,
+            state: e.state,
+            propagated,
+            lifted_to,
+            forbidden_by,
+            impl_edges,
+            text,
+        })
+    }
+
+    fn impl_edge_info(&self, edge_id: EdgeId) -> Option<ImplEdgeInfo> {
+        let e = self.edges.get(&edge_id)?;
+        Some(ImplEdgeInfo {
+            edge: edge_id,
+            kind: self.resolve_kind(e.kind).clone(),
+            state: e.state,
+            from: self.endpoint_info(e.from),
+            to: self.endpoint_info(e.to),
+            attrs: e.attrs.clone(),
+        })
+    }
+
+    fn endpoint_info(&self, node: NodeId) -> EndpointInfo {
+        //same walk as effective_mapping, remembering where the entry was found
+        let mut via = Some(node);
+        while let Some(n) = via {
+            if self.maps_to.contains_key(&n) {
+                break;
+            }
+            via = self.nodes.get(&n).and_then(|x| x.parent);
+        }
+        let arch_node = via.and_then(|v| self.maps_to.get(&v).copied());
+        EndpointInfo {
+            node,
+            path: self.node_path(node),
+            arch_node,
+            arch_path: arch_node.map(|a| self.node_path(a)),
+            mapped_via: via,
+        }
+    }
+
+    fn explanation_text(
+        &self,
+        e: &Edge,
+        propagated: &[EdgeId],
+        lifted_to: Option<EdgeId>,
+        forbidden_by: Option<RuleId>,
+        impl_edges: &[ImplEdgeInfo],
+    ) -> String {
+        let mut out = format!(
+            "{} -[{}]-> {} ({:?} edge #{}) is {:?}",
+            self.node_path(e.from),
+            self.resolve_kind(e.kind),
+            self.node_path(e.to),
+            e.subgraph,
+            e.id,
+            e.state
+        );
+
+        match (e.subgraph, lifted_to, forbidden_by) {
+            (SubgraphKind::Architecture, _, _) if impl_edges.is_empty() => {
+                out.push_str(": no implementation edge lifts onto it");
+            }
+            (SubgraphKind::Architecture, _, _) => {
+                let _ = write!(out, ": {} implementation edge(s) lift onto it", impl_edges.len());
+            }
+            (_, _, Some(rule)) => {
+                let _ = write!(out, ": forbidden by rule #{}", rule);
+            }
+            (_, Some(arch), _) => {
+                let a = &self.edges[&arch];
+                let _ = write!(
+                    out,
+                    ": lifted to architecture edge #{} ({} -[{}]-> {})",
+                    arch,
+                    self.node_path(a.from),
+                    self.resolve_kind(a.kind),
+                    self.node_path(a.to)
+                );
+            }
+            (SubgraphKind::Implementation, None, None) if propagated.is_empty() => {
+                out.push_str(": an endpoint is not mapped");
+            }
+            (SubgraphKind::Implementation, None, None) => {
+                let _ = write!(out, ": propagated edge #{} is not covered by an architecture edge", propagated[0]);
+            }
+            (SubgraphKind::Propagated, None, None) => {
+                out.push_str(": no architecture edge covers it");
+            }
+        }
+
+        for i in impl_edges {
+            let _ = write!(
+                out,
+                "\n  #{} {} -[{}]-> {}  ({}; {})",
+                i.edge,
+                i.from.path,
+                i.kind,
+                i.to.path,
+                self.describe_mapping(&i.from),
+                self.describe_mapping(&i.to)
+            );
+        }
+        out
+    }
+
+    fn describe_mapping(&self, ep: &EndpointInfo) -> String {
+        let name = self.nodes.get(&ep.node).map(|n| n.name.as_str()).unwrap_or_default();
+        match (&ep.arch_path, ep.mapped_via) {
+            (Some(arch), Some(via)) if via != ep.node => {
+                format!("{} => {} via {}", name, arch, self.node_path(via))
+            }
+            (Some(arch), _) => format!("{} => {}", name, arch),
+            (None, _) => format!("{} unmapped", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::builder::{EdgeBuilder, NodeBuilder};
+    use crate::core::graph::{GraphError, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn explains_convergent_divergent_and_absent_edges() {
+        let mut g = ReflexionGraph::new();
+        let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+        let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+        let db = NodeBuilder::architecture("DB").add_to(&mut g).unwrap();
+        let arch = EdgeBuilder::new(ui, service).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+        let absent = EdgeBuilder::new(service, db).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+
+        let pkg = NodeBuilder::implementation("ui").add_to(&mut g).unwrap();
+        let page = NodeBuilder::implementation("LoginPage").parent(pkg).add_to(&mut g).unwrap();
+        let users = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+        let repo = NodeBuilder::implementation("UserRepo").add_to(&mut g).unwrap();
+        g.set_mapping(pkg, ui).unwrap();
+        g.set_mapping(users, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+
+        let ok = EdgeBuilder::new(page, users).kind(EdgeKind::calls()).attr("line", 3i64).add_to(&mut g).unwrap();
+        let bad = EdgeBuilder::new(page, repo).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+
+        let ex = g.explain_edge(arch).unwrap();
+        assert_eq!(ex.state, EdgeState::Convergent);
+        assert_eq!(ex.impl_edges.len(), 1);
+        let info = &ex.impl_edges[0];
+        assert_eq!(info.edge, ok);
+        assert_eq!(info.from.path, "ui/LoginPage");
+        assert_eq!((info.from.arch_node, info.from.mapped_via), (Some(ui), Some(pkg)));
+        assert!(ex.text.contains("LoginPage => UI via ui"), "{}", ex.text);
+
+        //the divergent impl edge is explained through its propagated edge, but stays the subject
+        let ex = g.explain_edge(bad).unwrap();
+        assert_eq!((ex.edge, ex.subgraph), (bad, SubgraphKind::Implementation));
+        assert_eq!(ex.state, EdgeState::Divergent);
+        assert_eq!(ex.lifted_to, None);
+        assert_eq!(ex.impl_edges.iter().map(|i| i.edge).collect::<Vec<_>>(), vec![bad]);
+        assert!(ex.text.contains("is not covered by an architecture edge"), "{}", ex.text);
+
+        let prop = ex.propagated[0];
+        let ex = g.explain_edge(prop).unwrap();
+        assert_eq!((ex.edge, ex.subgraph), (prop, SubgraphKind::Propagated));
+        assert!(ex.text.contains("no architecture edge covers it"), "{}", ex.text);
+
+        let ok_prop = g.explain_edge(ok).unwrap();
+        assert_eq!(ok_prop.edge, ok);
+        assert_eq!(ok_prop.lifted_to, Some(arch));
+        assert_ne!(ok_prop.propagated[0], prop);
+        assert_eq!(g.propagated_edges_lifted_to(arch), ok_prop.propagated);
+        assert!(ok_prop.text.contains("lifted to architecture edge"), "{}", ok_prop.text);
+
+        let ex = g.explain_edge(absent).unwrap();
+        assert!(ex.impl_edges.is_empty());
+        assert!(ex.text.ends_with("no implementation edge lifts onto it"), "{}", ex.text);
+
+        assert_eq!(g.explain_edge(999), Err(GraphError::EdgeNotFound(999)));
+    }
+}
@@ -0,0 +1,249 @@
+// forbidden-edge rules ("UI must never call DB")
+//
+// A forbidden rule is stronger than "not specified": a propagated edge matching it is Forbidden
+// instead of Divergent, and it is checked before lifting, so not even a specified or allowed
+// edge can make it acceptable. Endpoints and kind may be wildcards (None = any), e.g.
+// "any -> LegacyDB". Rules follow the same hierarchy as lifting: a rule on a component also
+// covers its subcomponents, and a rule on a kind also covers its subkinds.
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::types::{EdgeId, EdgeKind, KindId, NodeId, RuleId, SubgraphKind};
+
+//ordered by id so the oldest matching rule is reported deterministically
+pub(crate) type ForbiddenRules = BTreeMap<RuleId, ForbiddenRule>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForbiddenRule {
+    pub from: Option<NodeId>, //None = any architecture node
+    pub to: Option<NodeId>,   //None = any architecture node
+    pub kind: Option<EdgeKind>, //None = any edge kind
+}
+
+impl ForbiddenRule {
+    pub fn new(from: NodeId, to: NodeId) -> Self {
+        Self { from: Some(from), to: Some(to), kind: None }
+    }
+
+    //"any -> to"
+    pub fn any_to(to: NodeId) -> Self {
+        Self { from: None, to: Some(to), kind: None }
+    }
+
+    //"from -> any"
+    pub fn from_any(from: NodeId) -> Self {
+        Self { from: Some(from), to: None, kind: None }
+    }
+
+    //restrict the rule to one edge kind (and its subkinds)
+    pub fn with_kind(mut self, kind: EdgeKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+}
+
+impl ReflexionGraph {
+    /// Incremental: add a forbidden rule and reclassify the propagated edges it matches.
+    pub fn add_forbidden_rule(&mut self, rule: ForbiddenRule) -> Result<RuleId, GraphError> {
+        for node in [rule.from, rule.to].into_iter().flatten() {
+            let found = self.node_subgraph(node)?;
+            if found != SubgraphKind::Architecture {
+                return Err(GraphError::WrongSubgraph {
+                    node,
+                    expected: SubgraphKind::Architecture,
+                    found,
+                });
+            }
+        }
+
+        let mut affected: Vec<EdgeId> = self
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Propagated && self.rule_matches(&rule, e.from, e.to, e.kind))
+            .map(|e| e.id)
+            .collect();
+        affected.sort_unstable();
+
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+
+        let kind = rule.kind.as_ref().map(|k| self.kinds.intern(k));
+        let id = self.next_rule_id;
+        self.next_rule_id += 1;
+        self.forbidden_rules.insert(id, rule);
+
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        //a rule on a kind keeps implementation edges of its subkinds at that kind
+        if let Some(kind) = kind
+            && self.use_kind(kind)
+        {
+            self.regroup_propagated_edges(|g, k| g.is_subkind_id(kind, k))?;
+        }
+
+        Ok(id)
+    }
+
+    /// Incremental: drop a forbidden rule; edges it forbade are lifted normally again.
+    pub fn remove_forbidden_rule(&mut self, rule_id: RuleId) -> Result<ForbiddenRule, GraphError> {
+        if !self.forbidden_rules.contains_key(&rule_id) {
+            return Err(GraphError::RuleNotFound(rule_id));
+        }
+
+        let mut affected: Vec<EdgeId> = self
+            .forbidden_by
+            .iter()
+            .filter(|&(_, &r)| r == rule_id)
+            .map(|(&prop_id, _)| prop_id)
+            .collect();
+        affected.sort_unstable();
+
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+
+        let rule = self.forbidden_rules.remove(&rule_id).expect("checked above");
+
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        if let Some(kind) = rule.kind.as_ref().and_then(|k| self.kinds.get(k))
+            && self.release_kind(kind)
+        {
+            self.regroup_propagated_edges(|g, k| g.is_subkind_id(kind, k))?;
+        }
+
+        Ok(rule)
+    }
+
+    pub fn forbidden_rules(&self) -> impl Iterator<Item = (RuleId, &ForbiddenRule)> + '_ {
+        self.forbidden_rules.iter().map(|(&id, r)| (id, r))
+    }
+
+    //the rule that forbade a propagated edge (None = not forbidden)
+    pub fn forbidding_rule_of(&self, prop_id: EdgeId) -> Option<RuleId> {
+        self.forbidden_by.get(&prop_id).copied()
+    }
+
+    //first rule (lowest id) forbidding a dependency from_arch -> to_arch of this kind
+    pub fn forbidding_rule(&self, from_arch: NodeId, to_arch: NodeId, kind: &EdgeKind) -> Option<RuleId> {
+        match self.kinds.get(kind) {
+            Some(kind) => self.forbidding_rule_id(from_arch, to_arch, kind),
+            //a kind never interned has no supertypes: only exact or kind-less rules apply
+            None => self
+                .forbidden_rules
+                .iter()
+                .find(|(_, rule)| {
+                    rule.kind.as_ref().is_none_or(|k| k == kind) && self.rule_endpoints_match(rule, from_arch, to_arch)
+                })
+                .map(|(&id, _)| id),
+        }
+    }
+
+    pub(crate) fn forbidding_rule_id(&self, from_arch: NodeId, to_arch: NodeId, kind: KindId) -> Option<RuleId> {
+        self.forbidden_rules
+            .iter()
+            .find(|(_, rule)| self.rule_matches(rule, from_arch, to_arch, kind))
+            .map(|(&id, _)| id)
+    }
+
+    fn rule_matches(&self, rule: &ForbiddenRule, from_arch: NodeId, to_arch: NodeId, kind: KindId) -> bool {
+        let kind_ok = rule
+            .kind
+            .as_ref()
+            .is_none_or(|k| self.kinds.get(k).is_some_and(|k| self.is_subkind_id(kind, k)));
+        kind_ok && self.rule_endpoints_match(rule, from_arch, to_arch)
+    }
+
+    fn rule_endpoints_match(&self, rule: &ForbiddenRule, from_arch: NodeId, to_arch: NodeId) -> bool {
+        let from_ok = rule.from.is_none_or(|f| self.ancestors_or_self(from_arch).contains(&f));
+        let to_ok = rule.to.is_none_or(|t| self.ancestors_or_self(to_arch).contains(&t));
+        from_ok && to_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ForbiddenRule;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn wildcard_rule_overrides_specified_edge() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let legacy = g.add_node(Node::new("LegacyDB", SubgraphKind::Architecture, None)).unwrap();
+        g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+        let arch_legacy = g.add_arch_edge(service, legacy, EdgeKind::calls()).unwrap();
+
+        let login = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let repo = g.add_node(Node::new("OldRepo", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(login, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        g.set_mapping(repo, legacy).unwrap();
+        let e_ok = g.add_impl_edge(login, usersvc, EdgeKind::calls()).unwrap();
+        let e_bad = g.add_impl_edge(usersvc, repo, EdgeKind::calls()).unwrap();
+        assert_eq!(g.count_violations(), 0);
+
+        //"any -> LegacyDB": even the specified Service -> LegacyDB edge cannot cover it
+        let rule = g.add_forbidden_rule(ForbiddenRule::any_to(legacy)).unwrap();
+        assert!(matches!(g.edges.get(&e_bad).unwrap().state, EdgeState::Forbidden));
+        assert!(matches!(g.edges.get(&arch_legacy).unwrap().state, EdgeState::Absent));
+        assert_eq!(g.edges.get(&arch_legacy).unwrap().counter, 0);
+        assert!(matches!(g.edges.get(&e_ok).unwrap().state, EdgeState::Convergent));
+        assert_eq!(g.count_forbidden(), 2); //impl + propagated edge
+        assert_eq!(g.count_violations(), 3); //... + the now absent arch edge
+
+        //from scratch agrees with the incremental result
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&e_bad).unwrap().state, EdgeState::Forbidden));
+        assert!(matches!(g.edges.get(&arch_legacy).unwrap().state, EdgeState::Absent));
+        assert_eq!(g.count_forbidden(), 2);
+
+        //removing the rule restores the ordinary classification
+        g.remove_forbidden_rule(rule).unwrap();
+        assert!(matches!(g.edges.get(&e_bad).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(g.edges.get(&arch_legacy).unwrap().state, EdgeState::Convergent));
+        assert_eq!(g.count_violations(), 0);
+        assert_eq!(g.remove_forbidden_rule(rule), Err(GraphError::RuleNotFound(rule)));
+    }
+
+    #[test]
+    fn rules_cover_subcomponents_and_subkinds() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let tables = g.add_node(Node::new("Tables", SubgraphKind::Architecture, Some(db))).unwrap();
+
+        let page = g.add_node(Node::new("Page", SubgraphKind::Implementation, None)).unwrap();
+        let table = g.add_node(Node::new("UserTable", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(table, tables).unwrap();
+
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.add_forbidden_rule(ForbiddenRule::new(ui, db).with_kind(EdgeKind::depends_on())).unwrap();
+        let e_calls = g.add_impl_edge(page, table, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&e_calls).unwrap().state, EdgeState::Forbidden));
+
+        //a kind outside the rule's hierarchy is ordinary drift
+        let e_other = g.add_impl_edge(page, table, EdgeKind::new("reads")).unwrap();
+        assert!(matches!(g.edges.get(&e_other).unwrap().state, EdgeState::Divergent));
+    }
+
+    #[test]
+    fn rule_endpoints_must_be_architecture_nodes() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let page = g.add_node(Node::new("Page", SubgraphKind::Implementation, None)).unwrap();
+        assert!(matches!(
+            g.add_forbidden_rule(ForbiddenRule::new(ui, page)),
+            Err(GraphError::WrongSubgraph { .. })
+        ));
+        assert_eq!(g.add_forbidden_rule(ForbiddenRule::from_any(99)), Err(GraphError::NodeNotFound(99)));
+    }
+}
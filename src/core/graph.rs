@@ -1,10 +1,16 @@
 // nodes, edges, IR 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
-use crate::core::types::{NodeId, EdgeId, Counter, SubgraphKind, EdgeKind};
+use serde::{Deserialize, Serialize};
+use crate::core::types::{NodeId, EdgeId, Counter, SubgraphKind, EdgeKind, NodeKind, RuleId, Attributes, AttrValue, KindId};
+use crate::core::interner::KindInterner;
+use crate::core::dense::DenseMap;
 use crate::core::state::EdgeState;
+use crate::core::forbidden::ForbiddenRules;
+use crate::core::layers::Layering;
+use crate::core::mapping::MappingRule;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GraphError {
     EdgeNotFound(EdgeId), 
     ParentNotFound(NodeId),
@@ -12,6 +18,18 @@ pub enum GraphError {
     WrongSubgraph { node: NodeId, expected: SubgraphKind, found: SubgraphKind },
     MappingAlreadyExists { impl_node: NodeId, old_arch: NodeId, new_arch: NodeId },
     ImplNodeAlreadyMapped(NodeId),
+    EdgeKindCycle { sub: EdgeKind, sup: EdgeKind },
+    EdgeKindParentExists { kind: EdgeKind, old_parent: EdgeKind, new_parent: EdgeKind },
+    RuleNotFound(RuleId),
+    NodeInMultipleLayers(NodeId),
+    InvalidPattern { pattern: String, message: String },
+    KindNotAllowed { impl_node: NodeId, kind: NodeKind, arch_node: NodeId },
+    UnsupportedSnapshotVersion { found: u32, supported: u32 },
+    Snapshot(String),
+    InvalidDocument(String),
+    Parse { line: usize, column: usize, message: String },
+    Io { path: String, message: String },
+    ModelRow { row: String, error: Box<GraphError> }, //e.g. row "implementation.edges[3]"
 }
 
 
@@ -44,68 +62,327 @@ impl fmt::Display for GraphError {
             GraphError::EdgeNotFound(id) => {
                 write!(f, "Edge not found (edge id = {})", id)
             }
+            GraphError::EdgeKindCycle { sub, sup } => {
+                write!(f, "Edge kind {} cannot be a subtype of {}: {} is already a subtype of {}", sub, sup, sup, sub)
+            }
+            GraphError::EdgeKindParentExists { kind, old_parent, new_parent } => {
+                write!(
+                    f,
+                    "Edge kind {} is already a subtype of {}; cannot make it a subtype of {}",
+                    kind, old_parent, new_parent
+                )
+            }
+            GraphError::RuleNotFound(id) => {
+                write!(f, "Rule not found (rule id = {})", id)
+            }
+            GraphError::NodeInMultipleLayers(id) => {
+                write!(f, "Node assigned to more than one layer (node id = {})", id)
+            }
+            GraphError::InvalidPattern { pattern, message } => {
+                write!(f, "Invalid pattern {:?}: {}", pattern, message)
+            }
+            GraphError::KindNotAllowed { impl_node, kind, arch_node } => {
+                write!(
+                    f,
+                    "Node {} of kind {} may not be mapped to architecture node {}",
+                    impl_node, kind, arch_node
+                )
+            }
+            GraphError::UnsupportedSnapshotVersion { found, supported } => {
+                write!(f, "Unsupported snapshot format version {} (supported: {})", found, supported)
+            }
+            GraphError::Snapshot(message) => {
+                write!(f, "Snapshot error: {}", message)
+            }
+            GraphError::InvalidDocument(message) => {
+                write!(f, "Invalid model document: {}", message)
+            }
+            GraphError::Parse { line, column, message } => {
+                write!(f, "Parse error at line {}, column {}: {}", line, column, message)
+            }
+            GraphError::Io { path, message } => {
+                write!(f, "I/O error on {}: {}", path, message)
+            }
+            GraphError::ModelRow { row, error } => {
+                write!(f, "In model row {}: {}", row, error)
+            }
         }
     }
 }
 
 impl std::error::Error for GraphError{} 
 
+impl GraphError {
+    //file and stream errors of every reader/writer (JSON models, snapshots)
+    pub(crate) fn io(path: &std::path::Path, e: std::io::Error) -> Self {
+        GraphError::Io { path: path.display().to_string(), message: e.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Node {
-     id: NodeId,
-    name: String,
-    subgraph: SubgraphKind,
-    parent: Option<NodeId>,
-    children: Vec<NodeId>,
+    pub(crate) id: NodeId,
+    pub(crate) name: String,
+    pub(crate) subgraph: SubgraphKind,
+    pub(crate) kind: NodeKind,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) children: Vec<NodeId>,
+    pub(crate) attrs: Attributes,
 }
 
 
 impl Node {
-    pub(crate) fn new(name: impl Into<String>, subgraph: SubgraphKind, parent: Option<NodeId>) -> Self {
+    //kind defaults to the generic ArchitectureNode / ImplementationNode, see with_kind
+    pub fn new(name: impl Into<String>, subgraph: SubgraphKind, parent: Option<NodeId>) -> Self {
+        let kind = match subgraph {
+            SubgraphKind::Implementation => NodeKind::ImplementationNode,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => NodeKind::ArchitectureNode,
+        };
         Self {
             id: 0, // overwritten by add_node
             name: name.into(),
             subgraph,
+            kind,
             parent,
             children: vec![],
+            attrs: Attributes::new(),
         }
     }
+
+    pub fn with_kind(mut self, kind: NodeKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_attr(mut self, key: impl Into<String>, value: impl Into<AttrValue>) -> Self {
+        self.attrs.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn subgraph(&self) -> SubgraphKind {
+        self.subgraph
+    }
+
+    pub fn kind(&self) -> &NodeKind {
+        &self.kind
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    pub fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Edge {
     pub(crate) id: EdgeId,
     pub(crate) from: NodeId,
     pub(crate) to: NodeId,
-    pub(crate) kind: EdgeKind,
+    pub(crate) kind: KindId,
     pub(crate) subgraph: SubgraphKind,
     pub(crate) state: EdgeState,
     pub(crate) counter: Counter,
+    pub(crate) attrs: Attributes,
 }
 
+//read-only view; edges are created through EdgeBuilder or the incremental API
+impl Edge {
+    pub fn id(&self) -> EdgeId {
+        self.id
+    }
+
+    pub fn from(&self) -> NodeId {
+        self.from
+    }
+
+    pub fn to(&self) -> NodeId {
+        self.to
+    }
+
+    //interned kind, only meaningful in the graph the edge came from: resolve it there with
+    //ReflexionGraph::edge_kind(edge id) or kind_name(kind id)
+    pub fn kind(&self) -> KindId {
+        self.kind
+    }
+
+    pub fn subgraph(&self) -> SubgraphKind {
+        self.subgraph
+    }
+
+    pub fn state(&self) -> EdgeState {
+        self.state
+    }
+
+    //architecture edge: implementation edges lifted onto it; propagated edge: impl edges behind it
+    pub fn counter(&self) -> Counter {
+        self.counter
+    }
+
+    pub fn attrs(&self) -> &Attributes {
+        &self.attrs
+    }
+}
+
+//(from, to, kind): lookup key for architecture and propagated edges
+pub(crate) type EdgeKey = (NodeId, NodeId, KindId);
+
 pub struct ReflexionGraph {
-    pub(crate) nodes: HashMap<NodeId, Node>,
-    pub(crate) edges: HashMap<EdgeId, Edge>,
-    pub(crate) impl_out: HashMap<NodeId, Vec<EdgeId>>,
-    pub(crate) arch_out: HashMap<NodeId, Vec<EdgeId>>,
+    pub(crate) nodes: DenseMap<Node>,
+    pub(crate) edges: DenseMap<Edge>,
+    pub(crate) impl_out: DenseMap<Vec<EdgeId>>,
+    pub(crate) impl_in: DenseMap<Vec<EdgeId>>,
+    pub(crate) arch_out: DenseMap<Vec<EdgeId>>,
+    pub(crate) arch_in: DenseMap<Vec<EdgeId>>,
+    pub(crate) arch_index: HashMap<EdgeKey, Vec<EdgeId>>,
+    pub(crate) prop_index: HashMap<EdgeKey, EdgeId>,
     pub maps_to: HashMap<NodeId, NodeId>,
     pub(crate) propagation_table: HashMap<EdgeId, HashSet<EdgeId>>,
+    pub(crate) impl_to_prop: HashMap<EdgeId, EdgeId>,
+    pub(crate) lifted_to: HashMap<EdgeId, EdgeId>,
+    pub(crate) lifted_from: HashMap<EdgeId, HashSet<EdgeId>>,
+    pub(crate) kind_parents: HashMap<KindId, KindId>,
+    pub(crate) kind_refs: HashMap<KindId, usize>,
+    pub(crate) kinds: KindInterner,
+    pub(crate) allowed_edges: HashSet<EdgeId>,
+    pub(crate) forbidden_rules: ForbiddenRules,
+    pub(crate) forbidden_by: HashMap<EdgeId, RuleId>,
+    pub(crate) next_rule_id: RuleId,
+    pub(crate) layering: Option<Layering>,
+    pub(crate) layer_edges: Vec<EdgeId>,
+    pub(crate) layer_subtype: bool,
+    pub(crate) mapping_rules: BTreeMap<RuleId, MappingRule>,
+    pub(crate) next_mapping_rule_id: RuleId,
+    pub(crate) rule_mapped: HashMap<NodeId, RuleId>,
+    pub(crate) mapping_conflicts: Vec<GraphError>,
+    pub(crate) kind_restrictions: HashMap<NodeId, HashSet<NodeKind>>,
     pub(crate) next_node_id: NodeId,
     pub(crate) next_edge_id: EdgeId,
+    pub(crate) free_propagated_ids: BTreeSet<EdgeId>,
+}
+
+impl Default for ReflexionGraph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ReflexionGraph {
     pub fn new() -> Self {
         Self {
-            nodes: HashMap::new(),
-            edges: HashMap::new(),
-            impl_out: HashMap::new(),
-            arch_out: HashMap::new(),
+            nodes: DenseMap::new(), //indexed by id, see dense.rs
+            edges: DenseMap::new(),
+            impl_out: DenseMap::new(),
+            impl_in: DenseMap::new(), //impl node -> incoming impl edges
+            arch_out: DenseMap::new(),
+            arch_in: DenseMap::new(), //arch node -> incoming architecture and propagated edges
+            arch_index: HashMap::new(), //(from, to, kind) -> architecture edges (specified and allowed)
+            prop_index: HashMap::new(), //(from, to, kind) -> the propagated edge
             maps_to: HashMap::new(),
             propagation_table: HashMap::new(), //arc/propagated edge -> impl edges
+            impl_to_prop: HashMap::new(), //reverse of propagation_table: impl edge -> propagated edge
+            lifted_to: HashMap::new(), //propagated edge -> architecture edge it lifted to
+            lifted_from: HashMap::new(), //reverse of lifted_to: arch edge -> propagated edges
+            kind_parents: HashMap::new(), //edge kind -> direct supertype (e.g. calls -> depends_on)
+            kind_refs: HashMap::new(), //edge kind -> architecture edges and forbidden rules using it
+            kinds: KindInterner::new(),
+            allowed_edges: HashSet::new(), //architecture edges that are permitted but not required
+            forbidden_rules: ForbiddenRules::new(),
+            forbidden_by: HashMap::new(), //propagated edge -> forbidden rule it matched
+            next_rule_id: 1, //forbidden rules
+            layering: None,
+            layer_edges: Vec::new(), //allowed edges derived from the layering
+            layer_subtype: false, //calls <: depends_on was registered by set_layering
+            mapping_rules: BTreeMap::new(),
+            next_mapping_rule_id: 1, //mapping rules count separately from forbidden rules
+            rule_mapped: HashMap::new(), //impl node -> mapping rule its maps_to entry came from
+            mapping_conflicts: Vec::new(),
+            kind_restrictions: HashMap::new(), //arch node -> node kinds that may map to it
             next_node_id: 1, 
             next_edge_id: 1,
+            free_propagated_ids: BTreeSet::new(), //during a full run: ids it dropped, reused lowest first
         }
     }
 
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&Edge> {
+        self.edges.get(&id)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    //all nodes, ordered by id
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> + '_ {
+        self.nodes.values()
+    }
+
+    //all edges, ordered by id
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges.values()
+    }
+
+    pub fn edges_in(&self, subgraph: SubgraphKind) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges().filter(move |e| e.subgraph == subgraph)
+    }
+
+    pub fn edges_with_state(&self, state: EdgeState) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges().filter(move |e| e.state == state)
+    }
+
+    pub fn edge_kind(&self, id: EdgeId) -> Result<&EdgeKind, GraphError> {
+        self.edges.get(&id).map(|e| self.resolve_kind(e.kind)).ok_or(GraphError::EdgeNotFound(id))
+    }
+
+    //edge kinds are interned; these convert between EdgeKind and KindId
+    pub fn intern_kind(&mut self, kind: &EdgeKind) -> KindId {
+        self.kinds.intern(kind)
+    }
+
+    pub fn kind_id(&self, kind: &EdgeKind) -> Option<KindId> {
+        self.kinds.get(kind)
+    }
+
+    //None for a KindId of another graph
+    pub fn kind_name(&self, id: KindId) -> Option<&EdgeKind> {
+        self.kinds.resolve(id)
+    }
+
+    //kinds stored on this graph's edges and rules were interned here
+    pub(crate) fn resolve_kind(&self, id: KindId) -> &EdgeKind {
+        self.kinds.resolve(id).expect("kind id interned by this graph")
+    }
+
+    pub fn edge_state(&self, id: EdgeId) -> Result<EdgeState, GraphError> {
+        self.edges.get(&id).map(|e| e.state).ok_or(GraphError::EdgeNotFound(id))
+    }
+
+    pub fn edge_counter(&self, id: EdgeId) -> Result<Counter, GraphError> {
+        self.edges.get(&id).map(|e| e.counter).ok_or(GraphError::EdgeNotFound(id))
+    }
+
     pub fn node_subgraph(&self, node: NodeId) -> Result<SubgraphKind, GraphError> {
         self.nodes
             .get(&node)
@@ -127,10 +404,10 @@ impl ReflexionGraph {
 
     pub fn add_node(&mut self, mut node: Node) -> Result<NodeId, GraphError> {
         //if parent is specified, it must already exist
-        if let Some(parent_id) = node.parent {
-            if !self.nodes.contains_key(&parent_id) {
-                return Err(GraphError::ParentNotFound(parent_id));
-            }
+        if let Some(parent_id) = node.parent
+            && !self.nodes.contains_key(&parent_id)
+        {
+            return Err(GraphError::ParentNotFound(parent_id));
         }
 
         //now graph owns identity, assign fresh IDs
@@ -145,6 +422,11 @@ impl ReflexionGraph {
             self.nodes.get_mut(&parent_id).expect("Checked Above").children.push(id);
         }
 
+        //rule based mapping picks up new implementation nodes right away
+        if self.nodes[&id].subgraph == SubgraphKind::Implementation {
+            self.map_new_node_by_rules(id);
+        }
+
         Ok(id)
     }
 
@@ -158,33 +440,52 @@ impl ReflexionGraph {
             return Err(GraphError::NodeNotFound(edge.to));
         }
 
-        //now graph owns identity, assign fresh IDs
-        let id = self.fresh_edge_id();
+        //now graph owns identity, assign fresh IDs. During a full run propagated edges take the
+        //ids of the ones the run dropped first, so reruns don't grow `edges`.
+        let id = match edge.subgraph {
+            SubgraphKind::Propagated => self.free_propagated_ids.pop_first(),
+            _ => None,
+        }
+        .unwrap_or_else(|| self.fresh_edge_id());
         edge.id = id;
 
         //insert edge
         self.edges.insert(id, edge);
+        self.index_edge(id);
+
+        Ok(id)
+    }
 
-        //update adjacency list based on subgraph
-        let edge_ref = self.edges.get(&id).expect("Just Inserted");
+    //adjacency + (from, to, kind) index entries for an edge already in `edges`
+    pub(crate) fn index_edge(&mut self, id: EdgeId) {
+        let edge_ref = self.edges.get(&id).expect("edge is stored");
 
         match edge_ref.subgraph {
             SubgraphKind::Implementation => {
-                self.impl_out.entry(edge_ref.from).or_default().push(id);
+                self.impl_out.get_or_insert_default(edge_ref.from).push(id);
+                self.impl_in.get_or_insert_default(edge_ref.to).push(id);
             } 
-            SubgraphKind::Architecture | SubgraphKind::Propagated => {
-                self.arch_out.entry(edge_ref.from).or_default().push(id);
+            SubgraphKind::Architecture => {
+                self.arch_out.get_or_insert_default(edge_ref.from).push(id);
+                self.arch_in.get_or_insert_default(edge_ref.to).push(id);
+                let key = (edge_ref.from, edge_ref.to, edge_ref.kind);
+                self.arch_index.entry(key).or_default().push(id);
+                self.use_kind(key.2);
+            }
+            SubgraphKind::Propagated => {
+                self.arch_out.get_or_insert_default(edge_ref.from).push(id);
+                self.arch_in.get_or_insert_default(edge_ref.to).push(id);
+                let key = (edge_ref.from, edge_ref.to, edge_ref.kind);
+                self.prop_index.insert(key, id);
             }
         }
-
-        Ok(id)
     }
 
     //Prepare the graph for a fresh reflexion analysis and run:
     // - Arch edges: Specified, Counter=0
     // - Impl edges: Undefined, Counter=0
     // - Propagated edges: Undefined, Counter=0
-    // - Propagation_table cleared
+    // - Propagation_table, lifted_to (+ reverse indexes) and forbidden_by cleared
     pub fn init_states(&mut self) {
         for edge in self.edges.values_mut() {
 
@@ -200,13 +501,17 @@ impl ReflexionGraph {
             }
         }
         self.propagation_table.clear();
+        self.impl_to_prop.clear();
+        self.lifted_to.clear();
+        self.lifted_from.clear();
+        self.forbidden_by.clear();
     }
 
     // Optional helper for future incremental modes:
     // remove all propagated edges from the graph.
     //
-    // NOTE: This intentionally only removes edges and their adjacency references
-    // for impl_out/arch_out. If you later add more adjacency indexes, update here too.
+    // NOTE: edges are removed through detach_edge, which keeps every adjacency list and
+    // index (arch_out, arch_index, prop_index, ...) consistent.
     pub fn clear_propagated_edges(&mut self) {
         // collect first to avoid borrowing issues while removing
         let to_remove: Vec<EdgeId> = self
@@ -214,7 +519,7 @@ impl ReflexionGraph {
             .iter()
             .filter_map(|(id, e)| {
                 if matches!(e.subgraph, SubgraphKind::Propagated) {
-                    Some(*id)
+                    Some(id)
                 } else {
                     None
                 }
@@ -222,40 +527,136 @@ impl ReflexionGraph {
             .collect();
 
         for eid in to_remove {
-            if let Some(e) = self.edges.remove(&eid) {
-                // remove from adjacency lists
-                if let Some(v) = self.impl_out.get_mut(&e.from) {
+            self.detach_edge(eid);
+        }
+    }
+
+    // Start of a full run: drop every propagated edge and let the run hand out their ids again.
+    // Outside a run ids are never reused, so an id a caller kept can't turn into another edge
+    // through an incremental update; the run discards every propagated id anyway.
+    pub(crate) fn recycle_propagated_edges(&mut self) {
+        let dropped: Vec<EdgeId> = self
+            .edges
+            .iter()
+            .filter(|(_, e)| e.subgraph == SubgraphKind::Propagated)
+            .map(|(id, _)| id)
+            .collect();
+        for &eid in &dropped {
+            self.detach_edge(eid);
+        }
+        self.free_propagated_ids = dropped.into_iter().collect();
+    }
+
+    // All implementation edges that start or end at `node` (ascending ids, self-loops once).
+    pub(crate) fn incident_impl_edges(&self, node: NodeId) -> Vec<EdgeId> {
+        self.impl_edges_touching(&HashSet::from([node]))
+    }
+
+    // Same as incident_impl_edges, for a whole set of nodes. Read from impl_out / impl_in,
+    // so the cost is the number of incident edges, not the size of the graph.
+    pub(crate) fn impl_edges_touching(&self, nodes: &HashSet<NodeId>) -> Vec<EdgeId> {
+        let mut out: Vec<EdgeId> = nodes
+            .iter()
+            .flat_map(|n| [self.impl_out.get(n), self.impl_in.get(n)])
+            .flatten()
+            .flatten()
+            .copied()
+            .collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    // Remove a single edge object plus its adjacency references and any propagation
+    // bookkeeping keyed by it. Does NOT touch states/counters of other edges; callers
+    // doing incremental updates are responsible for that.
+    pub(crate) fn detach_edge(&mut self, eid: EdgeId) -> Option<Edge> {
+        let e = self.edges.remove(&eid)?;
+
+        // remove from adjacency lists
+        if let Some(v) = self.impl_out.get_mut(&e.from) {
+            v.retain(|&x| x != eid);
+        }
+        if let Some(v) = self.impl_in.get_mut(&e.to) {
+            v.retain(|&x| x != eid);
+        }
+        if let Some(v) = self.arch_out.get_mut(&e.from) {
+            v.retain(|&x| x != eid);
+        }
+        if let Some(v) = self.arch_in.get_mut(&e.to) {
+            v.retain(|&x| x != eid);
+        }
+
+        // remove from the (from, to, kind) indexes
+        let key = (e.from, e.to, e.kind);
+        match e.subgraph {
+            SubgraphKind::Architecture => {
+                if let Some(v) = self.arch_index.get_mut(&key) {
                     v.retain(|&x| x != eid);
+                    if v.is_empty() {
+                        self.arch_index.remove(&key);
+                    }
                 }
-                if let Some(v) = self.arch_out.get_mut(&e.from) {
-                    v.retain(|&x| x != eid);
+                self.release_kind(e.kind);
+            }
+            SubgraphKind::Propagated => {
+                if self.prop_index.get(&key) == Some(&eid) {
+                    self.prop_index.remove(&key);
                 }
+            }
+            SubgraphKind::Implementation => {}
+        }
 
-                // remove any propagation bookkeeping referencing this edge id
-                self.propagation_table.remove(&eid);
+        // remove any propagation bookkeeping referencing this edge id (both directions)
+        if let Some(impls) = self.propagation_table.remove(&eid) {
+            for ie in impls {
+                self.impl_to_prop.remove(&ie);
             }
         }
+        if let Some(prop) = self.impl_to_prop.remove(&eid)
+            && let Some(impls) = self.propagation_table.get_mut(&prop)
+        {
+            impls.remove(&eid);
+        }
+        self.forget_lift(eid);
+        self.lifted_from.remove(&eid);
+        self.forbidden_by.remove(&eid);
+        self.allowed_edges.remove(&eid);
+
+        Some(e)
+    }
+
+    // Remove a single node object, unlink it from its parent's children list and drop its
+    // adjacency entries. Edges and mappings are the caller's business (see remove_node).
+    pub(crate) fn detach_node(&mut self, node: NodeId) -> Option<Node> {
+        let n = self.nodes.remove(&node)?;
+
+        if let Some(parent) = n.parent.and_then(|p| self.nodes.get_mut(&p)) {
+            parent.children.retain(|&c| c != node);
+        }
+        self.impl_out.remove(&node);
+        self.impl_in.remove(&node);
+        self.arch_out.remove(&node);
+        self.arch_in.remove(&node);
+        self.forget_mapping_node(node);
+        self.kind_restrictions.remove(&node);
+
+        Some(n)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::types::{EdgeKind, SubgraphKind};
+    use crate::core::types::{EdgeKind, KindId, SubgraphKind};
     use crate::core::state::EdgeState;
-    use std::collections::{HashMap, HashSet};
+    use std::collections::HashSet;
 
     fn mk_node(name: &str, subgraph: SubgraphKind, parent: Option<NodeId>) -> Node {
-        Node {
-            id: 0, // will be overwritten by add_node
-            name: name.to_string(),
-            subgraph,
-            parent,
-            children: vec![],
-        }
+        Node::new(name, subgraph, parent)
     }
 
-    fn mk_edge(from: NodeId, to: NodeId, subgraph: SubgraphKind, kind: EdgeKind) -> Edge {
+    fn mk_edge(from: NodeId, to: NodeId, subgraph: SubgraphKind, kind: KindId) -> Edge {
         Edge {
             id: 0, // will be overwritten by add_edge
             from,
@@ -264,6 +665,7 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         }
     }
 
@@ -308,11 +710,11 @@ mod tests {
 
         // 1 arch edge, 1 impl edge
         let e_arch = g
-            .add_edge(mk_edge(a1, a2, SubgraphKind::Architecture, EdgeKind::contains()))
+            .add_edge(mk_edge(a1, a2, SubgraphKind::Architecture, KindId::CONTAINS))
             .unwrap();
 
         let e_impl = g
-            .add_edge(mk_edge(i1, i2, SubgraphKind::Implementation, EdgeKind::calls()))
+            .add_edge(mk_edge(i1, i2, SubgraphKind::Implementation, KindId::CALLS))
             .unwrap();
 
         // arch_out contains e_arch at a1
@@ -332,7 +734,7 @@ mod tests {
 
         // to node missing
         let err = g
-            .add_edge(mk_edge(n1, 999, SubgraphKind::Architecture, EdgeKind::depends_on()))
+            .add_edge(mk_edge(n1, 999, SubgraphKind::Architecture, KindId::DEPENDS_ON))
             .unwrap_err();
 
         assert_eq!(err, GraphError::NodeNotFound(999));
@@ -357,10 +759,11 @@ mod tests {
                 id: e_arch,
                 from: n1,
                 to: n2,
-                kind: EdgeKind::depends_on(),
+                kind: KindId::DEPENDS_ON,
                 subgraph: SubgraphKind::Architecture,
                 state: EdgeState::Undefined, // wrong on purpose
                 counter: 7,                  // wrong on purpose
+                attrs: Default::default(),
             },
         );
 
@@ -370,10 +773,11 @@ mod tests {
                 id: e_impl,
                 from: n1,
                 to: n2,
-                kind: EdgeKind::depends_on(),
+                kind: KindId::DEPENDS_ON,
                 subgraph: SubgraphKind::Implementation,
                 state: EdgeState::Specified, // wrong on purpose
                 counter: 9,                  // wrong on purpose
+                attrs: Default::default(),
             },
         );
 
@@ -383,10 +787,11 @@ mod tests {
                 id: e_prop,
                 from: n1,
                 to: n2,
-                kind: EdgeKind::depends_on(),
+                kind: KindId::DEPENDS_ON,
                 subgraph: SubgraphKind::Propagated,
                 state: EdgeState::Specified, // wrong on purpose
                 counter: 3,                  // wrong on purpose
+                attrs: Default::default(),
             },
         );
 
@@ -411,4 +816,56 @@ mod tests {
 
         assert!(g.propagation_table.is_empty());
     }
+
+    #[test]
+    fn edge_indexes_follow_add_clear_and_remove() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(mk_node("B", SubgraphKind::Architecture, None)).unwrap();
+        let i1 = g.add_node(mk_node("I1", SubgraphKind::Implementation, None)).unwrap();
+        let i2 = g.add_node(mk_node("I2", SubgraphKind::Implementation, None)).unwrap();
+
+        let arch = g.add_edge(mk_edge(a, b, SubgraphKind::Architecture, KindId::CALLS)).unwrap();
+        let allowed = g.add_allowed_edge(a, b, EdgeKind::calls()).unwrap();
+        let key = (a, b, KindId::CALLS);
+        assert_eq!(g.arch_index[&key], vec![arch, allowed]);
+        assert_eq!(g.lift_exact(a, b, &EdgeKind::calls()).unwrap(), Some(arch));
+
+        g.set_mapping(i1, a).unwrap();
+        g.set_mapping(i2, b).unwrap();
+        let ie = g.add_impl_edge(i1, i2, EdgeKind::calls()).unwrap();
+        let back = g.add_impl_edge(i2, i1, EdgeKind::calls()).unwrap();
+        assert_eq!(g.impl_in[&i2], vec![ie]);
+        assert_eq!(g.incident_impl_edges(i1), vec![ie, back]);
+        let prop = g.propagated_edge_of(ie).unwrap();
+        assert_eq!(g.prop_index[&key], prop);
+        assert_eq!(g.get_or_create_propagated_edge(a, b, EdgeKind::calls()).unwrap(), prop);
+
+        g.clear_propagated_edges();
+        assert!(g.prop_index.is_empty());
+        g.remove_impl_edge(back).unwrap();
+        assert!(g.impl_in[&i1].is_empty());
+        assert_eq!(g.incident_impl_edges(i1), vec![ie]);
+
+        g.remove_arch_edge(arch).unwrap();
+        assert_eq!(g.arch_index[&key], vec![allowed]);
+        g.remove_arch_edge(allowed).unwrap();
+        assert!(g.arch_index.is_empty());
+    }
+
+    #[test]
+    fn edge_kinds_resolve_in_their_own_graph_only() {
+        let mut g = ReflexionGraph::new();
+        let i1 = g.add_node(mk_node("Impl1", SubgraphKind::Implementation, None)).unwrap();
+        let i2 = g.add_node(mk_node("Impl2", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_impl_edge(i1, i2, EdgeKind::from("imports")).unwrap();
+
+        let kind = g.edges().find(|x| x.id() == e).unwrap().kind();
+        assert_eq!(g.kind_name(kind), Some(&EdgeKind::from("imports")));
+        assert_eq!(g.edge_kind(e).unwrap(), &EdgeKind::from("imports"));
+
+        // another graph never interned it: no panic, just no answer
+        let other = ReflexionGraph::new();
+        assert_eq!(other.kind_name(kind), None);
+    }
 }
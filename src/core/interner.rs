@@ -0,0 +1,85 @@
+// edge kind interner, owned by the graph
+//
+// Edges store a KindId (a u32) instead of an EdgeKind string, so propagation copies and
+// compares integers and never allocates per edge. Every distinct kind is stored once. The
+// predefined kinds are seeded at fixed ids (KindId::CONTAINS, ::CALLS, ::DEPENDS_ON).
+use std::collections::HashMap;
+use crate::core::types::{EdgeKind, KindId};
+
+#[derive(Debug, Clone)]
+pub struct KindInterner {
+    ids: HashMap<EdgeKind, KindId>,
+    kinds: Vec<EdgeKind>, //indexed by KindId
+}
+
+impl Default for KindInterner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KindInterner {
+    pub fn new() -> Self {
+        let mut interner = Self { ids: HashMap::new(), kinds: Vec::new() };
+        //order must match the KindId constants
+        for kind in [EdgeKind::contains(), EdgeKind::calls(), EdgeKind::depends_on()] {
+            interner.intern(&kind);
+        }
+        interner
+    }
+
+    //id of `kind`, allocating only the first time a kind is seen
+    pub fn intern(&mut self, kind: &EdgeKind) -> KindId {
+        if let Some(&id) = self.ids.get(kind) {
+            return id;
+        }
+        let id = KindId(self.kinds.len() as u32);
+        self.kinds.push(kind.clone());
+        self.ids.insert(kind.clone(), id);
+        id
+    }
+
+    //lookup without interning (None = no edge of this kind was ever created)
+    pub fn get(&self, kind: &EdgeKind) -> Option<KindId> {
+        self.ids.get(kind).copied()
+    }
+
+    //None for an id this interner never handed out (e.g. one taken from another graph)
+    pub fn resolve(&self, id: KindId) -> Option<&EdgeKind> {
+        self.kinds.get(id.0 as usize)
+    }
+
+    //in id order
+    pub fn iter(&self) -> impl Iterator<Item = &EdgeKind> + '_ {
+        self.kinds.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KindInterner;
+    use crate::core::types::{EdgeKind, KindId};
+
+    #[test]
+    fn predefined_kinds_have_fixed_ids_and_interning_is_idempotent() {
+        let mut i = KindInterner::new();
+        assert_eq!(i.get(&EdgeKind::calls()), Some(KindId::CALLS));
+        assert_eq!(i.resolve(KindId::DEPENDS_ON), Some(&EdgeKind::depends_on()));
+        assert_eq!(i.intern(&EdgeKind::contains()), KindId::CONTAINS);
+
+        assert_eq!(i.get(&EdgeKind::from("imports")), None);
+        let imports = i.intern(&EdgeKind::from("imports"));
+        assert_eq!(i.intern(&EdgeKind::from("imports")), imports);
+        assert_eq!(i.resolve(imports).map(|k| k.as_str()), Some("imports"));
+        assert_eq!(i.len(), 4);
+        assert_eq!(KindInterner::new().resolve(imports), None);
+    }
+}
@@ -0,0 +1,174 @@
+// JSON reader/writer for model documents (see model.rs; schema in docs/model.schema.json)
+//
+// JSON is the interchange format for extractors written in other languages. Syntax and type
+// errors are reported as GraphError::Parse with the line and column in the input; errors in
+// the model itself (unknown node ids, wrong subgraphs, ...) are wrapped in GraphError::ModelRow
+// with the row they are about.
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::model::{ModelDocument, ModelImport};
+
+fn parse_error(e: serde_json::Error) -> GraphError {
+    //serde_json appends the position to its message; it is carried in the fields instead
+    let (line, column) = (e.line(), e.column());
+    let message = e.to_string();
+    let message = message
+        .strip_suffix(&format!(" at line {} column {}", line, column))
+        .unwrap_or(&message)
+        .to_string();
+    GraphError::Parse { line, column, message }
+}
+
+impl ModelDocument {
+    //pretty-printed, one row per line
+    pub fn to_json(&self) -> Result<String, GraphError> {
+        serde_json::to_string_pretty(self).map_err(|e| GraphError::InvalidDocument(e.to_string()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, GraphError> {
+        serde_json::from_str(text).map_err(parse_error)
+    }
+
+    pub fn read_json<R: Read>(reader: R) -> Result<Self, GraphError> {
+        serde_json::from_reader(reader).map_err(|e| match e.classify() {
+            serde_json::error::Category::Io => GraphError::Io { path: "<reader>".to_string(), message: e.to_string() },
+            _ => parse_error(e),
+        })
+    }
+}
+
+impl ReflexionGraph {
+    /// Architecture, implementation and mapping as a JSON model document; with `with_results`
+    /// the edge states and counters are included.
+    pub fn to_json(&self, with_results: bool) -> Result<String, GraphError> {
+        self.to_model(with_results).to_json()
+    }
+
+    /// Build and analyse a graph from a JSON model document (see `from_model`).
+    pub fn from_json(text: &str) -> Result<ModelImport, GraphError> {
+        ReflexionGraph::from_model(&ModelDocument::from_json(text)?)
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>, with_results: bool) -> Result<(), GraphError> {
+        let path = path.as_ref();
+        let file = fs::File::create(path).map_err(|e| GraphError::io(path, e))?;
+        let mut out = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut out, &self.to_model(with_results))
+            .map_err(|e| GraphError::InvalidDocument(e.to_string()))?;
+        out.flush().map_err(|e| GraphError::io(path, e))
+    }
+
+    pub fn load_json(path: impl AsRef<Path>) -> Result<ModelImport, GraphError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| GraphError::io(path, e))?;
+        ReflexionGraph::from_json(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::forbidden::ForbiddenRule;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::layers::Layering;
+    use crate::core::mapping::MappingRule;
+    use crate::core::model::{EdgeKindRow, ModelDocument};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{AttrValue, EdgeKind, NodeKind, SubgraphKind};
+
+    //every edge by endpoints and kind, with its state and counter
+    fn classification(g: &ReflexionGraph) -> Vec<String> {
+        let mut out: Vec<String> = g
+            .edges()
+            .map(|e| format!("{:?} {}->{} {} {:?} {}", e.subgraph(), e.from(), e.to(), g.edge_kind(e.id()).unwrap(), e.state(), e.counter()))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn round_trip_keeps_rules_layering_and_attributes() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let logs = g.add_node(Node::new("Logs", SubgraphKind::Architecture, None)).unwrap();
+        let x = g.add_node(Node::new("x", SubgraphKind::Implementation, None).with_attr("file", "x.rs")).unwrap();
+        let y = g.add_node(Node::new("y", SubgraphKind::Implementation, None)).unwrap();
+        let logger = g.add_node(Node::new("Logger", SubgraphKind::Implementation, None).with_kind(NodeKind::ClassNode)).unwrap();
+        g.set_mapping(x, ui).unwrap();
+        g.set_mapping(y, db).unwrap();
+        g.restrict_mapping_kinds(logs, [NodeKind::ClassNode]).unwrap();
+        g.add_mapping_rule(MappingRule::regex("^Log", logs).unwrap()).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+        g.set_layering(Layering::strict(vec![vec![ui], vec![db]])).unwrap();
+        g.add_forbidden_rule(ForbiddenRule::new(db, ui)).unwrap();
+        let down = g.add_impl_edge(x, y, EdgeKind::calls()).unwrap();
+        g.set_edge_attr(down, "line", 12).unwrap();
+        g.set_edge_attr(down, "weight", 0.5).unwrap();
+        let up = g.add_impl_edge(y, x, EdgeKind::calls()).unwrap();
+        g.add_impl_edge(x, y, EdgeKind::from("imports")).unwrap();
+        g.add_impl_edge(x, logger, EdgeKind::calls()).unwrap();
+        assert_eq!(g.edge_state(down).unwrap(), EdgeState::Allowed);
+        assert_eq!(g.edge_state(up).unwrap(), EdgeState::Forbidden);
+
+        let text = g.to_json(false).unwrap();
+        let doc = ModelDocument::from_json(&text).unwrap();
+        //what the layering and the mapping rule derive is not written out
+        assert!(doc.architecture.allowed.is_empty());
+        assert_eq!(doc.mapping.len(), 2);
+        assert_eq!(doc.edge_kinds, vec![EdgeKindRow { kind: "imports".to_string(), parent: "depends_on".to_string() }]);
+        assert!(text.contains("\"line\": 12"), "attributes are plain values:\n{}", text);
+
+        let imported = ReflexionGraph::from_json(&text).unwrap();
+        assert!(imported.node_ids.iter().all(|(doc_id, graph_id)| doc_id == graph_id));
+        let again = &imported.graph;
+        assert_eq!(classification(again), classification(&g));
+        assert_eq!(again.to_model(false), doc);
+        assert_eq!(again.get_arch_node(logger).unwrap(), Some(logs));
+        assert_eq!(again.node_attrs(x).unwrap().get("file"), Some(&AttrValue::from("x.rs")));
+        let down_again = again.edges_in(SubgraphKind::Implementation).find(|e| e.attrs().contains_key("line")).unwrap();
+        assert_eq!(again.edge_attrs(down_again.id()).unwrap(), g.edge_attrs(down).unwrap());
+
+        //the imported layering owns its calls <: depends_on as well: clearing it on both sides
+        //gives the same analysis again
+        assert_eq!(again.layering().unwrap().layers, vec![vec![ui], vec![db]]);
+        let mut again = imported.graph;
+        again.clear_layering().unwrap();
+        g.clear_layering().unwrap();
+        assert_eq!(classification(&again), classification(&g));
+    }
+
+    #[test]
+    fn errors_carry_line_and_column() {
+        let missing_field = "{\n  \"architecture\": {\n    \"nodes\": [\n      {\"id\": 1, \"parent\": null}\n    ]\n  }\n}";
+        match ModelDocument::from_json(missing_field) {
+            Err(GraphError::Parse { line, message, .. }) => {
+                assert_eq!(line, 4);
+                assert!(message.contains("name"), "{}", message);
+                assert!(!message.contains("at line"), "{}", message);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        let bad_state = "{\"results\": [\n{\"edge\": 1, \"subgraph\": \"Architecture\", \"from\": 1, \"to\": 2,\n \"kind\": \"calls\", \"state\": \"Fine\", \"counter\": 0}]}";
+        assert!(matches!(ModelDocument::from_json(bad_state), Err(GraphError::Parse { line: 3, .. })));
+        assert!(matches!(ModelDocument::from_json("{\"mapping\": [}"), Err(GraphError::Parse { line: 1, column: 14, .. })));
+        assert!(matches!(ModelDocument::read_json("[".as_bytes()), Err(GraphError::Parse { .. })));
+
+        //syntax is fine, the model is not: the model error comes with the row it is about
+        let dangling = r#"{"implementation": {"nodes": [{"id": 1, "name": "a"}], "edges": [{"from": 1, "to": 9, "kind": "calls"}]}}"#;
+        match ReflexionGraph::from_json(dangling) {
+            Err(GraphError::ModelRow { row, error }) => {
+                assert_eq!(row, "implementation.edges[0]");
+                assert_eq!(*error, GraphError::NodeNotFound(9));
+            }
+            other => panic!("expected a model row error, got {:?}", other.map(|_| ())),
+        }
+
+        assert!(matches!(
+            ReflexionGraph::load_json(std::env::temp_dir().join("no-such-reflexion-model.json")),
+            Err(GraphError::Io { .. })
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+// node kinds: queries, kind restrictions on mapping, and reports grouped by kind
+//
+// A kind restriction says which implementation node kinds may be mapped to an architecture
+// node ("only DatastoreNode may map to DB"). It is checked on explicit mappings (set_mapping,
+// set_mapping_overwrite and mapping rules); nodes that merely inherit a mapping from an
+// ancestor are not checked.
+use std::collections::{HashMap, HashSet};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::types::{NodeId, NodeKind, SubgraphKind};
+
+//per-kind summary of an analysed graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindReport {
+    pub kind: NodeKind,
+    pub nodes: Vec<NodeId>,
+    pub unmapped: usize, //implementation nodes without (implicit) mapping
+    pub edges: usize, //architecture/implementation edges leaving nodes of this kind
+    pub violations: usize, //... that are violations
+    pub forbidden: usize, //... that are Forbidden
+}
+
+impl ReflexionGraph {
+    pub fn node_kind(&self, node: NodeId) -> Result<&NodeKind, GraphError> {
+        self.nodes.get(&node).map(|n| &n.kind).ok_or(GraphError::NodeNotFound(node))
+    }
+
+    //changing the kind of a mapped node is checked against the restriction of its target
+    pub fn set_node_kind(&mut self, node: NodeId, kind: NodeKind) -> Result<NodeKind, GraphError> {
+        if !self.nodes.contains_key(&node) {
+            return Err(GraphError::NodeNotFound(node));
+        }
+        if let Some(&arch_node) = self.maps_to.get(&node)
+            && !self.kind_allows(arch_node, &kind)
+        {
+            return Err(GraphError::KindNotAllowed { impl_node: node, kind, arch_node });
+        }
+        let n = self.nodes.get_mut(&node).expect("checked above");
+        Ok(std::mem::replace(&mut n.kind, kind))
+    }
+
+    //e.g. all ServiceNodes, ordered by id
+    pub fn nodes_of_kind(&self, kind: &NodeKind) -> Vec<NodeId> {
+        let mut out: Vec<NodeId> = self.nodes.values().filter(|n| n.kind == *kind).map(|n| n.id).collect();
+        out.sort_unstable();
+        out
+    }
+
+    /// Only implementation nodes of one of `kinds` may be mapped to `arch_node` from now on.
+    /// Fails (and changes nothing) if an existing explicit mapping already breaks it.
+    pub fn restrict_mapping_kinds(
+        &mut self,
+        arch_node: NodeId,
+        kinds: impl IntoIterator<Item = NodeKind>,
+    ) -> Result<(), GraphError> {
+        let found = self.node_subgraph(arch_node)?;
+        if found != SubgraphKind::Architecture {
+            return Err(GraphError::WrongSubgraph {
+                node: arch_node,
+                expected: SubgraphKind::Architecture,
+                found,
+            });
+        }
+
+        let kinds: HashSet<NodeKind> = kinds.into_iter().collect();
+        let mut mapped: Vec<NodeId> = self
+            .maps_to
+            .iter()
+            .filter(|&(_, &a)| a == arch_node)
+            .map(|(&i, _)| i)
+            .collect();
+        mapped.sort_unstable();
+        for impl_node in mapped {
+            let kind = &self.nodes[&impl_node].kind;
+            if !kinds.contains(kind) {
+                return Err(GraphError::KindNotAllowed { impl_node, kind: kind.clone(), arch_node });
+            }
+        }
+
+        self.kind_restrictions.insert(arch_node, kinds);
+        Ok(())
+    }
+
+    pub fn clear_mapping_kind_restriction(&mut self, arch_node: NodeId) -> Option<HashSet<NodeKind>> {
+        self.kind_restrictions.remove(&arch_node)
+    }
+
+    pub fn mapping_kind_restriction(&self, arch_node: NodeId) -> Option<&HashSet<NodeKind>> {
+        self.kind_restrictions.get(&arch_node)
+    }
+
+    pub(crate) fn check_kind_allowed(&self, impl_node: NodeId, arch_node: NodeId) -> Result<(), GraphError> {
+        let kind = self.node_kind(impl_node)?;
+        if self.kind_allows(arch_node, kind) {
+            Ok(())
+        } else {
+            Err(GraphError::KindNotAllowed { impl_node, kind: kind.clone(), arch_node })
+        }
+    }
+
+    fn kind_allows(&self, arch_node: NodeId, kind: &NodeKind) -> bool {
+        self.kind_restrictions.get(&arch_node).is_none_or(|kinds| kinds.contains(kind))
+    }
+
+    /// Summary per node kind (ordered by kind name; a predefined kind comes before a custom
+    /// kind of the same name). Edges are attributed to their source node.
+    pub fn report_by_kind(&self) -> Vec<KindReport> {
+        let mut by_kind: HashMap<&NodeKind, KindReport> = HashMap::new();
+        let ids: Vec<NodeId> = self.nodes.keys().collect();
+
+        for id in ids {
+            let node = &self.nodes[&id];
+            let r = by_kind.entry(&node.kind).or_insert_with(|| KindReport {
+                kind: node.kind.clone(),
+                nodes: Vec::new(),
+                unmapped: 0,
+                edges: 0,
+                violations: 0,
+                forbidden: 0,
+            });
+            r.nodes.push(id);
+            if node.subgraph == SubgraphKind::Implementation && self.effective_mapping(id).is_none() {
+                r.unmapped += 1;
+            }
+        }
+
+        for e in self.edges.values() {
+            if e.subgraph == SubgraphKind::Propagated {
+                continue;
+            }
+            let Some(node) = self.nodes.get(&e.from) else { continue };
+            let r = by_kind.get_mut(&node.kind).expect("every node kind was collected");
+            r.edges += 1;
+            if e.state.is_violation() {
+                r.violations += 1;
+            }
+            if e.state == EdgeState::Forbidden {
+                r.forbidden += 1;
+            }
+        }
+
+        let mut report: Vec<KindReport> = by_kind.into_values().collect();
+        report.sort_by_cached_key(|r| (r.kind.to_string(), matches!(r.kind, NodeKind::Custom(_))));
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::mapping::MappingRule;
+    use crate::core::types::{EdgeKind, NodeKind, SubgraphKind};
+
+    #[test]
+    fn kinds_default_by_subgraph_and_can_be_queried() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g
+            .add_node(Node::new("UserService", SubgraphKind::Implementation, None).with_kind(NodeKind::ServiceNode))
+            .unwrap();
+        let other = g
+            .add_node(Node::new("OrderService", SubgraphKind::Implementation, None).with_kind(NodeKind::ServiceNode))
+            .unwrap();
+        let plain = g.add_node(Node::new("Helper", SubgraphKind::Implementation, None)).unwrap();
+
+        assert_eq!(g.node_kind(ui).unwrap(), &NodeKind::ArchitectureNode);
+        assert_eq!(g.node_kind(plain).unwrap(), &NodeKind::ImplementationNode);
+        assert_eq!(g.nodes_of_kind(&NodeKind::ServiceNode), vec![svc, other]);
+
+        assert_eq!(g.set_node_kind(plain, NodeKind::custom("Util")).unwrap(), NodeKind::ImplementationNode);
+        assert_eq!(g.nodes_of_kind(&NodeKind::custom("Util")), vec![plain]);
+        assert_eq!(g.node_kind(99), Err(GraphError::NodeNotFound(99)));
+    }
+
+    #[test]
+    fn restricted_components_only_accept_allowed_kinds() {
+        let mut g = ReflexionGraph::new();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let store = g
+            .add_node(Node::new("UserStore", SubgraphKind::Implementation, None).with_kind(NodeKind::DatastoreNode))
+            .unwrap();
+        let svc = g
+            .add_node(Node::new("UserService", SubgraphKind::Implementation, None).with_kind(NodeKind::ServiceNode))
+            .unwrap();
+
+        g.restrict_mapping_kinds(db, [NodeKind::DatastoreNode]).unwrap();
+        g.set_mapping(store, db).unwrap();
+        assert_eq!(
+            g.set_mapping(svc, db),
+            Err(GraphError::KindNotAllowed { impl_node: svc, kind: NodeKind::ServiceNode, arch_node: db })
+        );
+        assert!(g.set_mapping_overwrite(svc, db).is_err());
+        assert!(g.set_node_kind(store, NodeKind::ServiceNode).is_err());
+
+        //mapping rules skip targets that reject the node's kind
+        g.add_mapping_rule(MappingRule::regex("^User", db).unwrap().with_priority(1)).unwrap();
+        g.add_mapping_rule(MappingRule::regex("Service$", service).unwrap()).unwrap();
+        assert_eq!(g.get_arch_node(svc).unwrap(), Some(service));
+
+        //a restriction that existing mappings already break is refused
+        assert!(g.restrict_mapping_kinds(service, [NodeKind::DatastoreNode]).is_err());
+        assert!(g.mapping_kind_restriction(service).is_none());
+    }
+
+    #[test]
+    fn report_groups_nodes_and_edges_by_kind() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let page = g
+            .add_node(Node::new("LoginPage", SubgraphKind::Implementation, None).with_kind(NodeKind::UINode))
+            .unwrap();
+        let store = g
+            .add_node(Node::new("UserStore", SubgraphKind::Implementation, None).with_kind(NodeKind::DatastoreNode))
+            .unwrap();
+        let orphan = g
+            .add_node(Node::new("Orphan", SubgraphKind::Implementation, None).with_kind(NodeKind::UINode))
+            .unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(store, db).unwrap();
+        g.add_impl_edge(page, store, EdgeKind::calls()).unwrap(); //divergent
+
+        let report = g.report_by_kind();
+        let kinds: Vec<String> = report.iter().map(|r| r.kind.to_string()).collect();
+        assert_eq!(kinds, ["ArchitectureNode", "DatastoreNode", "UINode"]);
+
+        let ui_nodes = &report[2];
+        assert_eq!(ui_nodes.nodes, vec![page, orphan]);
+        assert_eq!(ui_nodes.unmapped, 1);
+        assert_eq!(ui_nodes.edges, 1);
+        assert_eq!(ui_nodes.violations, 1);
+        assert_eq!(report[1].edges, 0);
+    }
+
+    #[test]
+    fn custom_kind_named_like_a_predefined_one_gets_its_own_row() {
+        let mut g = ReflexionGraph::new();
+        let svc = g
+            .add_node(Node::new("UserService", SubgraphKind::Implementation, None).with_kind(NodeKind::ServiceNode))
+            .unwrap();
+        let fake = g
+            .add_node(Node::new("OrderService", SubgraphKind::Implementation, None).with_kind(NodeKind::custom("ServiceNode")))
+            .unwrap();
+
+        let report = g.report_by_kind();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].kind, NodeKind::ServiceNode);
+        assert_eq!(report[0].nodes, vec![svc]);
+        assert_eq!(report[1].kind, NodeKind::custom("ServiceNode"));
+        assert_eq!(report[1].nodes, vec![fake]);
+    }
+}
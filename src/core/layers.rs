@@ -0,0 +1,309 @@
+// layering constraints on top of the architecture graph
+//
+// Instead of spelling out every pairwise edge, architecture nodes are assigned to ordered
+// layers (index 0 = top, e.g. UI > Service > DB). Downward dependencies are derived as allowed
+// architecture edges, so lifting classifies them as Allowed (AllowedAbsent when unused), while
+// upward or skipping dependencies find no covering edge and stay Divergent.
+//  - Strict:  a layer may only depend on the layer directly below it
+//  - Relaxed: a layer may depend on any layer below it
+// Dependencies inside one layer are not derived; add them explicitly if they are intended.
+// Derived edges are depends_on unless the layering says otherwise; so that plain `calls`
+// dependencies are covered by them, set_layering registers calls <: depends_on if calls has
+// no supertype yet; clearing the layering removes that registration again.
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::types::{EdgeId, EdgeKind, KindId, NodeId, SubgraphKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LayeringMode {
+    Strict,
+    Relaxed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layering {
+    pub mode: LayeringMode,
+    pub layers: Vec<Vec<NodeId>>, //top to bottom
+    pub kind: EdgeKind, //kind of the derived edges (covers its subkinds when lifting)
+}
+
+impl Layering {
+    //derived edges are depends_on, covering calls (see set_layering) and any other registered
+    //subkind; use with_kind for another kind
+    pub fn new(mode: LayeringMode, layers: Vec<Vec<NodeId>>) -> Self {
+        Self { mode, layers, kind: EdgeKind::depends_on() }
+    }
+
+    pub fn strict(layers: Vec<Vec<NodeId>>) -> Self {
+        Self::new(LayeringMode::Strict, layers)
+    }
+
+    pub fn relaxed(layers: Vec<Vec<NodeId>>) -> Self {
+        Self::new(LayeringMode::Relaxed, layers)
+    }
+
+    pub fn with_kind(mut self, kind: EdgeKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    //(upper layer, lower layer) pairs a dependency may go along
+    fn allowed_layer_pairs(&self) -> Vec<(usize, usize)> {
+        let n = self.layers.len();
+        (0..n)
+            .flat_map(|upper| {
+                let lowest = match self.mode {
+                    LayeringMode::Strict => (upper + 2).min(n),
+                    LayeringMode::Relaxed => n,
+                };
+                (upper + 1..lowest).map(move |lower| (upper, lower))
+            })
+            .collect()
+    }
+}
+
+impl ReflexionGraph {
+    /// Incremental: install a layering, replacing the previous one (and its derived edges).
+    ///
+    /// Returns the allowed architecture edges derived from it. The layers are checked first: a
+    /// non-architecture node or a node in two layers fails before anything changes. With the
+    /// default `depends_on` kind, `calls <: depends_on` is registered unless `calls` already has
+    /// a supertype, so a bare `Layering::strict(..)` allows downward calls; `clear_layering`
+    /// removes that registration again.
+    pub fn set_layering(&mut self, layering: Layering) -> Result<Vec<EdgeId>, GraphError> {
+        let mut seen = HashSet::new();
+        for &node in layering.layers.iter().flatten() {
+            let found = self.node_subgraph(node)?;
+            if found != SubgraphKind::Architecture {
+                return Err(GraphError::WrongSubgraph {
+                    node,
+                    expected: SubgraphKind::Architecture,
+                    found,
+                });
+            }
+            if !seen.insert(node) {
+                return Err(GraphError::NodeInMultipleLayers(node));
+            }
+        }
+
+        self.clear_layering()?;
+
+        if layering.kind == EdgeKind::depends_on() && !self.kind_parents.contains_key(&KindId::CALLS) {
+            self.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on())?;
+            self.layer_subtype = true;
+        }
+
+        let mut derived = Vec::new();
+        for (upper, lower) in layering.allowed_layer_pairs() {
+            for &from in &layering.layers[upper] {
+                for &to in &layering.layers[lower] {
+                    derived.push(self.add_allowed_edge(from, to, layering.kind.clone())?);
+                }
+            }
+        }
+
+        self.layer_edges = derived.clone();
+        self.layering = Some(layering);
+        Ok(derived)
+    }
+
+    /// Incremental: drop the layering, the allowed edges derived from it and the
+    /// `calls <: depends_on` registration `set_layering` added, if any.
+    pub fn clear_layering(&mut self) -> Result<Option<Layering>, GraphError> {
+        for eid in std::mem::take(&mut self.layer_edges) {
+            if self.edges.contains_key(&eid) {
+                self.remove_arch_edge(eid)?;
+            }
+        }
+        if std::mem::take(&mut self.layer_subtype) {
+            self.remove_edge_kind_subtype(KindId::CALLS)?;
+        }
+        Ok(self.layering.take())
+    }
+
+    pub fn layering(&self) -> Option<&Layering> {
+        self.layering.as_ref()
+    }
+
+    //edges derived from the current layering
+    pub fn layer_edges(&self) -> &[EdgeId] {
+        &self.layer_edges
+    }
+
+    //index of the layer a node was assigned to (0 = top)
+    pub fn layer_of(&self, node: NodeId) -> Option<usize> {
+        self.layering
+            .as_ref()?
+            .layers
+            .iter()
+            .position(|layer| layer.contains(&node))
+    }
+
+    //called when an architecture node is removed (its derived edges are already gone)
+    pub(crate) fn forget_layer_node(&mut self, node: NodeId) {
+        if let Some(layering) = self.layering.as_mut() {
+            for layer in &mut layering.layers {
+                layer.retain(|&n| n != node);
+            }
+        }
+        let edges = &self.edges;
+        self.layer_edges.retain(|eid| edges.contains_key(eid));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Layering;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+
+    struct Layers {
+        g: ReflexionGraph,
+        ui: NodeId,
+        service: NodeId,
+        db: NodeId,
+        page: NodeId,
+        usersvc: NodeId,
+        repo: NodeId,
+    }
+
+    //UI > Service > DB with one implementation node mapped to each layer
+    fn three_layers() -> Layers {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let repo = g.add_node(Node::new("UserRepo", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+        Layers { g, ui, service, db, page, usersvc, repo }
+    }
+
+    #[test]
+    fn strict_layering_allows_only_the_adjacent_lower_layer() {
+        let Layers { mut g, ui, service, db, page, usersvc, repo } = three_layers();
+        let derived = g
+            .set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]]).with_kind(EdgeKind::calls()))
+            .unwrap();
+        assert_eq!(derived.len(), 2);
+
+        let down = g.add_impl_edge(page, usersvc, EdgeKind::calls()).unwrap();
+        let skip = g.add_impl_edge(page, repo, EdgeKind::calls()).unwrap();
+        let up = g.add_impl_edge(repo, usersvc, EdgeKind::calls()).unwrap();
+
+        assert!(matches!(g.edges.get(&down).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&skip).unwrap().state, EdgeState::Divergent));
+        assert!(matches!(g.edges.get(&up).unwrap().state, EdgeState::Divergent));
+        //Service -> DB is permitted but unused: not a violation
+        assert_eq!(g.count_violations(), 4); //two impl + two propagated divergent edges
+        assert_eq!(g.layer_of(db), Some(2));
+    }
+
+    #[test]
+    fn default_layering_kind_covers_calls() {
+        let Layers { mut g, ui, service, db, page, usersvc, repo } = three_layers();
+        g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        assert!(g.is_edge_subkind(&EdgeKind::calls(), &EdgeKind::depends_on()));
+
+        let down = g.add_impl_edge(page, usersvc, EdgeKind::calls()).unwrap();
+        let up = g.add_impl_edge(repo, usersvc, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&down).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&up).unwrap().state, EdgeState::Divergent));
+
+        //a full run agrees
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&down).unwrap().state, EdgeState::Allowed));
+
+        //clearing the layering removes the registration again
+        g.clear_layering().unwrap();
+        assert!(!g.is_edge_subkind(&EdgeKind::calls(), &EdgeKind::depends_on()));
+        assert!(matches!(g.edges.get(&down).unwrap().state, EdgeState::Divergent));
+    }
+
+    #[test]
+    fn clearing_the_layering_keeps_explicit_kind_registrations() {
+        let Layers { mut g, ui, service, db, page, usersvc, .. } = three_layers();
+        let spec = g.add_arch_edge(ui, service, EdgeKind::depends_on()).unwrap();
+        let call = g.add_impl_edge(page, usersvc, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&call).unwrap().state, EdgeState::Divergent));
+
+        //the layering's registration must not outlive it: the depends_on edge stops covering calls
+        g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        assert!(matches!(g.edges.get(&call).unwrap().state, EdgeState::Convergent));
+        g.clear_layering().unwrap();
+        assert!(matches!(g.edges.get(&call).unwrap().state, EdgeState::Divergent));
+        assert!(matches!(g.edges.get(&spec).unwrap().state, EdgeState::Absent));
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&call).unwrap().state, EdgeState::Divergent));
+        assert_eq!(g.count_violations(), 3); //impl + propagated divergent edge, absent spec
+
+        //registered by the caller as well: clearing the layering leaves it alone
+        g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.clear_layering().unwrap();
+        assert!(g.is_edge_subkind(&EdgeKind::calls(), &EdgeKind::depends_on()));
+        assert!(matches!(g.edges.get(&call).unwrap().state, EdgeState::Convergent));
+    }
+
+    #[test]
+    fn relaxed_layering_allows_any_lower_layer() {
+        let Layers { mut g, ui, service, db, page, usersvc, repo } = three_layers();
+        let derived = g.set_layering(Layering::relaxed(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        assert_eq!(derived.len(), 3);
+
+        let skip = g.add_impl_edge(page, repo, EdgeKind::calls()).unwrap();
+        let up = g.add_impl_edge(usersvc, page, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&skip).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&up).unwrap().state, EdgeState::Divergent));
+
+        //from scratch agrees
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&skip).unwrap().state, EdgeState::Allowed));
+        assert!(matches!(g.edges.get(&up).unwrap().state, EdgeState::Divergent));
+    }
+
+    #[test]
+    fn replacing_the_layering_replaces_derived_edges() {
+        let Layers { mut g, ui, service, db, page, repo, .. } = three_layers();
+        g.set_layering(Layering::relaxed(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        let skip = g.add_impl_edge(page, repo, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&skip).unwrap().state, EdgeState::Allowed));
+
+        let derived = g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        assert!(matches!(g.edges.get(&skip).unwrap().state, EdgeState::Divergent));
+        assert_eq!(g.layer_edges(), derived.as_slice());
+
+        g.clear_layering().unwrap();
+        assert!(g.layering().is_none());
+        assert!(derived.iter().all(|eid| !g.edges.contains_key(eid)));
+    }
+
+    #[test]
+    fn removing_a_layer_node_forgets_it() {
+        let Layers { mut g, ui, service, db, .. } = three_layers();
+        g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        g.remove_node(db).unwrap();
+        assert_eq!(g.layer_of(db), None);
+        assert_eq!(g.layer_edges().len(), 1);
+        g.clear_layering().unwrap();
+    }
+
+    #[test]
+    fn invalid_layers_are_rejected() {
+        let Layers { mut g, ui, service, page, .. } = three_layers();
+        assert_eq!(
+            g.set_layering(Layering::strict(vec![vec![ui], vec![service, ui]])),
+            Err(GraphError::NodeInMultipleLayers(ui))
+        );
+        assert!(matches!(
+            g.set_layering(Layering::strict(vec![vec![ui], vec![page]])),
+            Err(GraphError::WrongSubgraph { .. })
+        ));
+        assert!(g.layering().is_none());
+    }
+}
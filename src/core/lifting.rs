@@ -10,29 +10,303 @@ Divergent: implemented ∧ not specified
 
 Absent: specified ∧ not implemented (handled later)
 
+Allowed edges are architecture edges that are permitted but not required (`allowed_edges`):
+implemented -> Allowed (instead of Convergent), not implemented -> AllowedAbsent (instead of
+Absent). Either way they are never violations.
+
+Forbidden rules (see forbidden.rs) are checked before lifting: a matching propagated edge is
+Forbidden and does not count toward any architecture edge.
+
+Hierarchical lifting: architecture nodes nest (layer > subsystem > component), so a propagated
+edge A.x -> B.y is also covered by a specified edge between ancestors, e.g. A -> B. The most
+specific covering edge wins; the chosen edge is recorded in `lifted_to`.
+
+Edge kinds form a subtype hierarchy too (`calls`, `imports` <: `depends_on`), so a specified
+`depends_on` edge covers propagated `calls` edges. Node specificity is tried first, then kind
+specificity. Propagated edges are shared along the hierarchy as well: implementation edges are
+propagated with their propagation kind (see `propagation_kind`), so with the architecture
+modelled at `depends_on`, `calls` and `imports` end up in one propagated `depends_on` edge.
+
 */
 use std::collections::HashSet;
-use std::ops::Sub;
-use crate::core::graph::{GraphError, ReflexionGraph, Edge};
+use crate::core::graph::{GraphError, ReflexionGraph};
 use crate::core::state::EdgeState;
-use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind};
+use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind, KindId};
 
 impl ReflexionGraph {
     //find an architecture graph that exactly matches (from, to, kind). if found return EdgeId, else none
-    
+    //a specified edge wins over an allowed edge with the same (from, to, kind)
     pub fn lift_exact(&self, from_arch: NodeId, to_arch: NodeId, kind: &EdgeKind) -> Result<Option<EdgeId>, GraphError> {
-        //search outgoing edges from this arch node (covers architecture and propagation)
-        if let Some(out) = self.arch_out.get(&from_arch) {
-            for &eid in out {
-                let e = self.edges.get(&eid).ok_or(GraphError::EdgeNotFound(eid))?;
-                if e.subgraph == SubgraphKind::Architecture && e.to == to_arch && &e.kind == kind {
+        match self.kinds.get(kind) {
+            Some(kind) => self.lift_exact_id(from_arch, to_arch, kind),
+            None => Ok(None), //no edge of this kind exists at all
+        }
+    }
+
+    pub(crate) fn lift_exact_id(&self, from_arch: NodeId, to_arch: NodeId, kind: KindId) -> Result<Option<EdgeId>, GraphError> {
+        let mut allowed = None;
+
+        //architecture edges with exactly this (from, to, kind), in insertion order
+        if let Some(candidates) = self.arch_index.get(&(from_arch, to_arch, kind)) {
+            for &eid in candidates {
+                if !self.allowed_edges.contains(&eid) {
                     return Ok(Some(eid));
                 }
+                allowed = allowed.or(Some(eid));
+            }
+        }
+        Ok(allowed)
+    }
+
+    pub fn is_allowed_edge(&self, arch_eid: EdgeId) -> bool {
+        self.allowed_edges.contains(&arch_eid)
+    }
+
+    //state of an architecture edge with implementation behind it; propagated and impl edges
+    //lifted onto it share that state
+    pub(crate) fn implemented_state(&self, arch_eid: EdgeId) -> EdgeState {
+        if self.is_allowed_edge(arch_eid) {
+            EdgeState::Allowed
+        } else {
+            EdgeState::Convergent
+        }
+    }
+
+    //state of an architecture edge nothing lifted onto
+    pub(crate) fn unimplemented_state(&self, arch_eid: EdgeId) -> EdgeState {
+        if self.is_allowed_edge(arch_eid) {
+            EdgeState::AllowedAbsent
+        } else {
+            EdgeState::Absent
+        }
+    }
+
+    //hierarchical lift: find the most specific architecture edge whose endpoints are
+    //ancestors-or-self of (from_arch, to_arch) and whose kind is kind-or-supertype. candidates are
+    //tried by total distance up the two parent chains, for equal distance the one closer to
+    //from_arch first, and per node pair the nearest kind first.
+    pub fn lift(&self, from_arch: NodeId, to_arch: NodeId, kind: &EdgeKind) -> Result<Option<EdgeId>, GraphError> {
+        match self.kinds.get(kind) {
+            Some(kind) => self.lift_id(from_arch, to_arch, kind),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn lift_id(&self, from_arch: NodeId, to_arch: NodeId, kind: KindId) -> Result<Option<EdgeId>, GraphError> {
+        let from_chain = self.ancestors_or_self(from_arch);
+        let to_chain = self.ancestors_or_self(to_arch);
+        let kind_chain = self.kind_and_supertypes(kind);
+
+        for distance in 0..(from_chain.len() + to_chain.len()).saturating_sub(1) {
+            for (i, &from) in from_chain.iter().enumerate().take(distance + 1) {
+                let Some(&to) = to_chain.get(distance - i) else {
+                    continue;
+                };
+                for &k in &kind_chain {
+                    if let Some(eid) = self.lift_exact_id(from, to, k)? {
+                        return Ok(Some(eid));
+                    }
+                }
             }
         }
         Ok(None)
     }
 
+    //register `sub` as a direct subtype of `sup` (e.g. calls <: depends_on).
+    //each kind has at most one direct supertype and cycles are rejected. propagated edges whose
+    //kind is (now) below `sub` are re-lifted and, where their propagation kind changed, merged
+    //right away, so an analysed graph stays consistent.
+    pub fn add_edge_kind_subtype(&mut self, sub: EdgeKind, sup: EdgeKind) -> Result<(), GraphError> {
+        let sub_id = self.kinds.intern(&sub);
+        let sup_id = self.kinds.intern(&sup);
+        if let Some(&old_parent) = self.kind_parents.get(&sub_id) {
+            if old_parent == sup_id {
+                //idempotent; registered explicitly now, so clearing the layering keeps it
+                if sub_id == KindId::CALLS {
+                    self.layer_subtype = false;
+                }
+                return Ok(());
+            }
+            let old_parent = self.resolve_kind(old_parent).clone();
+            return Err(GraphError::EdgeKindParentExists { kind: sub, old_parent, new_parent: sup });
+        }
+        if self.is_subkind_id(sup_id, sub_id) {
+            return Err(GraphError::EdgeKindCycle { sub, sup });
+        }
+
+        let mut affected: Vec<EdgeId> = self
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Propagated && self.is_subkind_id(e.kind, sub_id))
+            .map(|e| e.id)
+            .collect();
+        affected.sort_unstable();
+
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+        self.kind_parents.insert(sub_id, sup_id);
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        //kinds below `sub` may now share a propagated edge with a supertype
+        self.regroup_propagated_edges(|g, kind| g.is_subkind_id(kind, sub_id))
+    }
+
+    //undo add_edge_kind_subtype for `sub` (only set_layering's own registration is undone).
+    //propagated edges below `sub` are re-lifted, and implementation edges that shared a
+    //propagated edge with a former supertype get their own again.
+    pub(crate) fn remove_edge_kind_subtype(&mut self, sub_id: KindId) -> Result<(), GraphError> {
+        let Some(&sup_id) = self.kind_parents.get(&sub_id) else {
+            return Ok(());
+        };
+
+        let mut affected: Vec<EdgeId> = self
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Propagated && self.is_subkind_id(e.kind, sub_id))
+            .map(|e| e.id)
+            .collect();
+        affected.sort_unstable();
+
+        for &prop_id in &affected {
+            self.unlift_propagated_edge(prop_id)?;
+        }
+        self.kind_parents.remove(&sub_id);
+        for &prop_id in &affected {
+            self.lift_propagated_edge(prop_id)?;
+        }
+        self.regroup_propagated_edges(|g, kind| g.is_subkind_id(sup_id, kind))
+    }
+
+    //Kind of the propagated edge an implementation edge of `kind` contributes to: the most
+    //specific kind in kind_and_supertypes that an architecture edge or a forbidden rule uses,
+    //or the most general one if none is used. Kinds below it are used by neither, so lifting
+    //and forbidden rules give the same result for it as for `kind`.
+    pub(crate) fn propagation_kind(&self, kind: KindId) -> KindId {
+        let mut current = kind;
+        while !self.kind_refs.contains_key(&current) {
+            match self.kind_parents.get(&current) {
+                Some(&parent) => current = parent,
+                None => break,
+            }
+        }
+        current
+    }
+
+    //kind_refs bookkeeping; true when `kind` was not used before / is no longer used
+    pub(crate) fn use_kind(&mut self, kind: KindId) -> bool {
+        let refs = self.kind_refs.entry(kind).or_default();
+        *refs += 1;
+        *refs == 1
+    }
+
+    pub(crate) fn release_kind(&mut self, kind: KindId) -> bool {
+        match self.kind_refs.get_mut(&kind) {
+            Some(refs) if *refs > 1 => {
+                *refs -= 1;
+                false
+            }
+            Some(_) => {
+                self.kind_refs.remove(&kind);
+                true
+            }
+            None => false,
+        }
+    }
+
+    //reflexive + transitive: calls <: calls, calls <: depends_on
+    pub fn is_edge_subkind(&self, sub: &EdgeKind, sup: &EdgeKind) -> bool {
+        match (self.kinds.get(sub), self.kinds.get(sup)) {
+            (Some(sub), Some(sup)) => self.is_subkind_id(sub, sup),
+            _ => sub == sup, //a kind never registered has no supertypes
+        }
+    }
+
+    pub(crate) fn is_subkind_id(&self, sub: KindId, sup: KindId) -> bool {
+        let mut current = Some(sub);
+        while let Some(k) = current {
+            if k == sup {
+                return true;
+            }
+            current = self.kind_parents.get(&k).copied();
+        }
+        false
+    }
+
+    //[kind, parent, grandparent, ...]
+    pub(crate) fn kind_and_supertypes(&self, kind: KindId) -> Vec<KindId> {
+        let mut chain = vec![kind];
+        while let Some(&parent) = self.kind_parents.get(chain.last().expect("never empty")) {
+            chain.push(parent);
+        }
+        chain
+    }
+
+    //the propagated edge an implementation edge contributes to (None = unmapped/not propagated)
+    pub fn propagated_edge_of(&self, impl_edge_id: EdgeId) -> Option<EdgeId> {
+        self.impl_to_prop.get(&impl_edge_id).copied()
+    }
+
+    //the architecture edge a propagated edge is currently lifted to (None = divergent/not lifted)
+    pub fn lifted_arch_edge(&self, prop_id: EdgeId) -> Option<EdgeId> {
+        self.lifted_to.get(&prop_id).copied()
+    }
+
+    //propagated edges currently lifted to an architecture edge, in id order
+    pub fn propagated_edges_lifted_to(&self, arch_eid: EdgeId) -> Vec<EdgeId> {
+        let mut props: Vec<EdgeId> = self.lifted_from.get(&arch_eid).into_iter().flatten().copied().collect();
+        props.sort_unstable();
+        props
+    }
+
+    //lifted_to + its reverse index lifted_from, always updated together
+    pub(crate) fn record_lift(&mut self, prop_id: EdgeId, arch_eid: EdgeId) {
+        if let Some(old) = self.lifted_to.insert(prop_id, arch_eid)
+            && old != arch_eid
+            && let Some(props) = self.lifted_from.get_mut(&old)
+        {
+            props.remove(&prop_id);
+        }
+        self.lifted_from.entry(arch_eid).or_default().insert(prop_id);
+    }
+
+    pub(crate) fn forget_lift(&mut self, prop_id: EdgeId) -> Option<EdgeId> {
+        let arch_eid = self.lifted_to.remove(&prop_id)?;
+        if let Some(props) = self.lifted_from.get_mut(&arch_eid) {
+            props.remove(&prop_id);
+            if props.is_empty() {
+                self.lifted_from.remove(&arch_eid);
+            }
+        }
+        Some(arch_eid)
+    }
+
+    //[node, parent, grandparent, ...]
+    pub(crate) fn ancestors_or_self(&self, node: NodeId) -> Vec<NodeId> {
+        let mut chain = vec![node];
+        let mut current = self.nodes.get(&node).and_then(|n| n.parent);
+        while let Some(p) = current {
+            chain.push(p);
+            current = self.nodes.get(&p).and_then(|n| n.parent);
+        }
+        chain
+    }
+
+    //node plus everything below it in the containment tree
+    pub(crate) fn descendants_or_self(&self, node: NodeId) -> HashSet<NodeId> {
+        let mut out = HashSet::new();
+        let mut stack = vec![node];
+        while let Some(n) = stack.pop() {
+            if out.insert(n)
+                && let Some(node) = self.nodes.get(&n)
+            {
+                stack.extend(node.children.iter().copied());
+            }
+        }
+        out
+    }
+
     //propagate one impl edge -> then lift it against specified architecture edges 
     pub fn propagate_and_lift(&mut self, impl_edge_id: EdgeId) -> Result<(), GraphError> {
         //1) propagate (creates/reuses propagated edge + counter++ + propagation_table entry)
@@ -46,34 +320,54 @@ impl ReflexionGraph {
         }
 
         //2) find the propagated edge id corresponding to this impl edge 
-        let prop_id = self.propagation_table.iter().find_map( |(&prop_eid, impls)| {
-            if impls.contains(&impl_edge_id) {
-                Some(prop_eid)
-            } else {
-                None 
-            }
-        }).ok_or(GraphError::EdgeNotFound(impl_edge_id))?; //"not found in table" -> treat as edge not found-ish
+        let prop_id = self
+            .propagated_edge_of(impl_edge_id)
+            .ok_or(GraphError::EdgeNotFound(impl_edge_id))?; //"not found in table" -> treat as edge not found-ish
 
         //3) read propagated edge endpoints/kind 
         let (from_arch, to_arch, kind) = {
             let pe = self.edges.get(&prop_id).ok_or(GraphError::EdgeNotFound(prop_id))?;
-            (pe.from, pe.to, pe.kind.clone())
+            (pe.from, pe.to, pe.kind)
+        };
+
+        //4) forbidden rules come first: a forbidden dependency is never lifted
+        if !self.lifted_to.contains_key(&prop_id) {
+            let rule = match self.forbidden_by.get(&prop_id) {
+                Some(&rule) => Some(rule),
+                None => self.forbidding_rule_id(from_arch, to_arch, kind),
+            };
+            if let Some(rule) = rule {
+                self.forbidden_by.insert(prop_id, rule);
+                if let Some(pe) = self.edges.get_mut(&prop_id) {
+                    pe.state = EdgeState::Forbidden;
+                }
+                if let Some(ie) = self.edges.get_mut(&impl_edge_id) {
+                    ie.state = EdgeState::Forbidden;
+                }
+                return Ok(());
+            }
+        }
+
+        //5) lift: match propagated edge to specified architecture edge (reuse an earlier lift)
+        let target = match self.lifted_to.get(&prop_id) {
+            Some(&arch_eid) => Some(arch_eid),
+            None => self.lift_id(from_arch, to_arch, kind)?,
         };
 
-        //4) lift: match propagated edge to specified architecture edge 
-        if let Some(arch_eid) = self.lift_exact(from_arch, to_arch, &kind)? {
-            //architecture edge is convergent 
+        if let Some(arch_eid) = target {
+            self.record_lift(prop_id, arch_eid);
+
+            //architecture edge is convergent (allowed edge: allowed), propagated + impl follow it
+            let state = self.implemented_state(arch_eid);
             if let Some(ae) = self.edges.get_mut(&arch_eid) {
                 ae.counter += 1;
-                ae.state = EdgeState::Convergent;
+                ae.state = state;
             }
-
-            //propagated +impl are allowed
             if let Some(pe) = self.edges.get_mut(&prop_id) {
-                pe.state = EdgeState::Allowed;
+                pe.state = state;
             }
             if let Some(ie) = self.edges.get_mut(&impl_edge_id) {
-                ie.state = EdgeState::Allowed;
+                ie.state = state;
             }
         } else {
             //divergent
@@ -86,20 +380,86 @@ impl ReflexionGraph {
         }
         Ok(())
     }
+
+    //lift a whole propagated edge at once (used when the architecture side changes):
+    //the matching arch edge absorbs the full counter, and the propagated edge plus every impl
+    //edge behind it get the same state
+    pub fn lift_propagated_edge(&mut self, prop_id: EdgeId) -> Result<(), GraphError> {
+        let (from_arch, to_arch, kind, counter) = {
+            let pe = self.edges.get(&prop_id).ok_or(GraphError::EdgeNotFound(prop_id))?;
+            (pe.from, pe.to, pe.kind, pe.counter)
+        };
+
+        if let Some(rule) = self.forbidding_rule_id(from_arch, to_arch, kind) {
+            self.forbidden_by.insert(prop_id, rule);
+            self.set_propagated_state(prop_id, EdgeState::Forbidden);
+            return Ok(());
+        }
+
+        let state = match self.lift_id(from_arch, to_arch, kind)? {
+            Some(arch_eid) => {
+                self.record_lift(prop_id, arch_eid);
+                let state = self.implemented_state(arch_eid);
+                if let Some(ae) = self.edges.get_mut(&arch_eid) {
+                    ae.counter += counter;
+                    if ae.counter > 0 {
+                        ae.state = state;
+                    }
+                }
+                state
+            }
+            None => EdgeState::Divergent,
+        };
+
+        self.set_propagated_state(prop_id, state);
+        Ok(())
+    }
+
+    //inverse of lift_propagated_edge: take the propagated edge's counter back from the arch
+    //edge it lifted to (or forget the rule that forbade it). States of the propagated/impl
+    //edges are left for the next lift.
+    pub fn unlift_propagated_edge(&mut self, prop_id: EdgeId) -> Result<(), GraphError> {
+        let counter = self.edges.get(&prop_id).ok_or(GraphError::EdgeNotFound(prop_id))?.counter;
+        self.forbidden_by.remove(&prop_id);
+
+        if let Some(arch_eid) = self.forget_lift(prop_id) {
+            let absent = self.unimplemented_state(arch_eid);
+            if let Some(ae) = self.edges.get_mut(&arch_eid) {
+                ae.counter -= counter;
+                if ae.counter == 0 {
+                    ae.state = absent;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_propagated_state(&mut self, prop_id: EdgeId, state: EdgeState) {
+        if let Some(pe) = self.edges.get_mut(&prop_id) {
+            pe.state = state;
+        }
+        if let Some(impls) = self.propagation_table.get(&prop_id) {
+            for ie in impls {
+                if let Some(e) = self.edges.get_mut(ie) {
+                    e.state = state;
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::forbidden::ForbiddenRule;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
     use crate::core::state::EdgeState;
-    use crate::core::types::{EdgeKind, SubgraphKind};
+    use crate::core::types::{EdgeKind, KindId, SubgraphKind};
 
     fn mk_node(name: &str, subgraph: SubgraphKind) -> Node {
         Node::new(name, subgraph, None)
     }
 
-    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: EdgeKind) -> Edge {
+    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: KindId) -> Edge {
         Edge {
             id: 0, // overwritten by add_edge
             from,
@@ -108,6 +468,7 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         }
     }
 
@@ -131,7 +492,7 @@ mod tests {
 
         // Specified arch edge UI -> Service
         let arch_edge_id = g
-            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, EdgeKind::calls()))
+            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::CALLS))
             .unwrap();
 
         // Impl edge LoginPage -> UserService
@@ -140,7 +501,7 @@ mod tests {
                 login_page,
                 user_service,
                 SubgraphKind::Implementation,
-                EdgeKind::calls(),
+                KindId::CALLS,
             ))
             .unwrap();
 
@@ -159,11 +520,11 @@ mod tests {
         assert!(matches!(ae.state, EdgeState::Convergent));
         assert_eq!(ae.counter, 1);
 
-        // Assert impl edge allowed
+        // Assert impl edge convergent
         let ie = g.edges.get(&impl_edge_id).unwrap();
-        assert!(matches!(ie.state, EdgeState::Allowed));
+        assert!(matches!(ie.state, EdgeState::Convergent));
 
-        // Assert propagated edge exists and is allowed
+        // Assert propagated edge exists and is convergent
         let prop_id = g
             .propagation_table
             .iter()
@@ -173,7 +534,192 @@ mod tests {
         let pe = g.edges.get(&prop_id).unwrap();
         assert_eq!(pe.from, ui);
         assert_eq!(pe.to, service);
-        assert!(matches!(pe.state, EdgeState::Allowed));
+        assert!(matches!(pe.state, EdgeState::Convergent));
         assert_eq!(pe.counter, 1);
     }
+
+    #[test]
+    fn nested_propagated_edge_lifts_to_ancestor_edge_and_records_it() {
+        let mut g = ReflexionGraph::new();
+
+        // Frontend > Views, Backend > (Api, Store)
+        let frontend = g.add_node(mk_node("Frontend", SubgraphKind::Architecture)).unwrap();
+        let views = g
+            .add_node(Node::new("Views", SubgraphKind::Architecture, Some(frontend)))
+            .unwrap();
+        let backend = g.add_node(mk_node("Backend", SubgraphKind::Architecture)).unwrap();
+        let store = g
+            .add_node(Node::new("Store", SubgraphKind::Architecture, Some(backend)))
+            .unwrap();
+
+        let e_fb = g
+            .add_edge(mk_edge(frontend, backend, SubgraphKind::Architecture, KindId::CALLS))
+            .unwrap();
+
+        let page = g.add_node(mk_node("Page", SubgraphKind::Implementation)).unwrap();
+        let repo = g.add_node(mk_node("Repo", SubgraphKind::Implementation)).unwrap();
+        g.set_mapping(page, views).unwrap();
+        g.set_mapping(repo, store).unwrap();
+        let e_impl = g
+            .add_edge(mk_edge(page, repo, SubgraphKind::Implementation, KindId::CALLS))
+            .unwrap();
+
+        g.run_from_scratch().unwrap();
+
+        let ae = g.edges.get(&e_fb).unwrap();
+        assert!(matches!(ae.state, EdgeState::Convergent));
+        assert_eq!(ae.counter, 1);
+        assert!(matches!(g.edges.get(&e_impl).unwrap().state, EdgeState::Convergent));
+
+        let prop_id = *g.propagation_table.keys().next().unwrap();
+        let pe = g.edges.get(&prop_id).unwrap();
+        assert_eq!((pe.from, pe.to), (views, store));
+        assert_eq!(g.lifted_arch_edge(prop_id), Some(e_fb));
+
+        // a different kind is not covered by the ancestor edge
+        assert_eq!(g.lift(views, store, &EdgeKind::depends_on()).unwrap(), None);
+    }
+
+    #[test]
+    fn lift_prefers_the_most_specific_covering_edge() {
+        let mut g = ReflexionGraph::new();
+
+        let frontend = g.add_node(mk_node("Frontend", SubgraphKind::Architecture)).unwrap();
+        let views = g
+            .add_node(Node::new("Views", SubgraphKind::Architecture, Some(frontend)))
+            .unwrap();
+        let backend = g.add_node(mk_node("Backend", SubgraphKind::Architecture)).unwrap();
+        let store = g
+            .add_node(Node::new("Store", SubgraphKind::Architecture, Some(backend)))
+            .unwrap();
+
+        let e_fb = g
+            .add_edge(mk_edge(frontend, backend, SubgraphKind::Architecture, KindId::CALLS))
+            .unwrap();
+        let e_fs = g
+            .add_edge(mk_edge(frontend, store, SubgraphKind::Architecture, KindId::CALLS))
+            .unwrap();
+        let e_vb = g
+            .add_edge(mk_edge(views, backend, SubgraphKind::Architecture, KindId::CALLS))
+            .unwrap();
+
+        // one step up on either side beats two steps; on a tie the source side stays specific
+        assert_eq!(g.lift(views, store, &EdgeKind::calls()).unwrap(), Some(e_vb));
+        assert_eq!(g.lift(frontend, store, &EdgeKind::calls()).unwrap(), Some(e_fs));
+        assert_eq!(g.lift(frontend, backend, &EdgeKind::calls()).unwrap(), Some(e_fb));
+        assert_eq!(g.lift(backend, frontend, &EdgeKind::calls()).unwrap(), None);
+    }
+
+    #[test]
+    fn depends_on_spec_covers_subkinds_and_registry_changes_relift() {
+        let mut g = ReflexionGraph::new();
+
+        let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
+        let service = g.add_node(mk_node("Service", SubgraphKind::Architecture)).unwrap();
+        let e_dep = g
+            .add_edge(mk_edge(ui, service, SubgraphKind::Architecture, KindId::DEPENDS_ON))
+            .unwrap();
+
+        let page = g.add_node(mk_node("Page", SubgraphKind::Implementation)).unwrap();
+        let svc = g.add_node(mk_node("Svc", SubgraphKind::Implementation)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(svc, service).unwrap();
+        let e_call = g
+            .add_edge(mk_edge(page, svc, SubgraphKind::Implementation, KindId::CALLS))
+            .unwrap();
+        let imports = g.intern_kind(&EdgeKind::from("imports"));
+        let e_import = g
+            .add_edge(mk_edge(page, svc, SubgraphKind::Implementation, imports))
+            .unwrap();
+
+        // without the registry, kinds must match exactly
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&e_call).unwrap().state, EdgeState::Divergent));
+        assert!(matches!(g.edges.get(&e_dep).unwrap().state, EdgeState::Absent));
+
+        // registering after the run re-lifts the affected propagated edges right away
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+        assert!(matches!(g.edges.get(&e_call).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(g.edges.get(&e_import).unwrap().state, EdgeState::Convergent));
+        let ae = g.edges.get(&e_dep).unwrap();
+        assert!(matches!(ae.state, EdgeState::Convergent));
+        assert_eq!(ae.counter, 2);
+
+        // both now share one propagated depends_on edge, in a full run too
+        assert_eq!(g.propagation_table.len(), 1);
+        let prop = g.propagated_edge_of(e_call).unwrap();
+        assert_eq!(g.propagated_edge_of(e_import), Some(prop));
+        assert_eq!(g.edges.get(&prop).unwrap().kind, KindId::DEPENDS_ON);
+        assert_eq!(g.edges.get(&prop).unwrap().counter, 2);
+        g.run_from_scratch().unwrap();
+        assert_eq!(g.edges.get(&e_dep).unwrap().counter, 2);
+        assert_eq!(g.propagation_table.len(), 1);
+    }
+
+    #[test]
+    fn propagated_edges_split_and_merge_with_the_kinds_in_use() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(mk_node("UI", SubgraphKind::Architecture)).unwrap();
+        let service = g.add_node(mk_node("Service", SubgraphKind::Architecture)).unwrap();
+        let page = g.add_node(mk_node("Page", SubgraphKind::Implementation)).unwrap();
+        let svc = g.add_node(mk_node("Svc", SubgraphKind::Implementation)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(svc, service).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+
+        let e_call = g.add_impl_edge(page, svc, EdgeKind::calls()).unwrap();
+        let e_import = g.add_impl_edge(page, svc, EdgeKind::from("imports")).unwrap();
+        let shared = g.propagated_edge_of(e_call).unwrap();
+        assert_eq!(g.propagated_edge_of(e_import), Some(shared));
+        assert_eq!(g.edges.get(&shared).unwrap().counter, 2);
+
+        // a specified calls edge needs calls kept apart from imports
+        let arch_calls = g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+        let prop_calls = g.propagated_edge_of(e_call).unwrap();
+        assert_ne!(g.propagated_edge_of(e_import), Some(prop_calls));
+        assert!(matches!(g.edges.get(&e_call).unwrap().state, EdgeState::Convergent));
+        assert!(matches!(g.edges.get(&e_import).unwrap().state, EdgeState::Divergent));
+        assert_eq!(g.edges.get(&arch_calls).unwrap().counter, 1);
+
+        // ... and they merge again once it is gone
+        g.remove_arch_edge(arch_calls).unwrap();
+        assert_eq!(g.propagation_table.len(), 1);
+        assert_eq!(g.propagated_edge_of(e_call), g.propagated_edge_of(e_import));
+
+        // a forbidden rule on a kind splits the same way
+        let rule = g.add_forbidden_rule(ForbiddenRule::new(ui, service).with_kind(EdgeKind::calls())).unwrap();
+        assert!(matches!(g.edges.get(&e_call).unwrap().state, EdgeState::Forbidden));
+        assert!(matches!(g.edges.get(&e_import).unwrap().state, EdgeState::Divergent));
+        assert_eq!(g.propagation_table.len(), 2);
+        g.remove_forbidden_rule(rule).unwrap();
+        assert_eq!(g.propagation_table.len(), 1);
+        assert_eq!(g.count_forbidden(), 0);
+    }
+
+    #[test]
+    fn edge_kind_registry_rejects_cycles_and_second_parents() {
+        let mut g = ReflexionGraph::new();
+        let imports = EdgeKind::from("imports");
+
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        assert!(g.is_edge_subkind(&EdgeKind::calls(), &EdgeKind::depends_on()));
+        assert!(g.is_edge_subkind(&EdgeKind::calls(), &EdgeKind::calls()));
+        assert!(!g.is_edge_subkind(&EdgeKind::depends_on(), &EdgeKind::calls()));
+
+        assert_eq!(
+            g.add_edge_kind_subtype(EdgeKind::depends_on(), EdgeKind::calls()).unwrap_err(),
+            GraphError::EdgeKindCycle { sub: EdgeKind::depends_on(), sup: EdgeKind::calls() }
+        );
+        assert_eq!(
+            g.add_edge_kind_subtype(EdgeKind::calls(), imports.clone()).unwrap_err(),
+            GraphError::EdgeKindParentExists {
+                kind: EdgeKind::calls(),
+                old_parent: EdgeKind::depends_on(),
+                new_parent: imports,
+            }
+        );
+    }
 }
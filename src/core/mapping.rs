@@ -1,10 +1,125 @@
 // maps_to + rule based mapping
-use crate::core::types::NodeId;
-use crate::core::graph::Node;
+use std::collections::HashSet;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::core::types::{NodeId, RuleId};
 use crate::core::graph::ReflexionGraph;
 use crate::core::graph::GraphError;
 use crate::core::types::SubgraphKind;
 
+//what a mapping rule's pattern is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MatchOn {
+    Name, //the node's own name, e.g. "LoginPage"
+    Path, //names from the root down, joined with '/', e.g. "src/ui/LoginPage"
+}
+
+//"src/ui/** -> UI": every implementation node matching `pattern` is mapped to `target`.
+//Among matching rules the highest priority wins; on equal priority the older rule (lower id)
+//wins and the disagreement is reported as a conflict.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "SavedMappingRule", try_from = "SavedMappingRule")]
+pub struct MappingRule {
+    pub pattern: String, //as written by the user (glob or regex)
+    regex: Regex,
+    pub on: MatchOn,
+    pub target: NodeId,
+    pub priority: i32,
+}
+
+impl MappingRule {
+    //glob on the node path: `*` and `?` stay within one path segment, `**` spans segments
+    pub fn glob(pattern: impl Into<String>, target: NodeId) -> Self {
+        let pattern = pattern.into();
+        let regex = Regex::new(&glob_to_regex(&pattern)).expect("escaped glob is a valid regex");
+        Self { pattern, regex, on: MatchOn::Path, target, priority: 0 }
+    }
+
+    //regex on the node name (unanchored, use ^...$ for full matches)
+    pub fn regex(pattern: impl Into<String>, target: NodeId) -> Result<Self, GraphError> {
+        let pattern = pattern.into();
+        let regex = Regex::new(&pattern).map_err(|e| GraphError::InvalidPattern {
+            pattern: pattern.clone(),
+            message: e.to_string(),
+        })?;
+        Ok(Self { pattern, regex, on: MatchOn::Name, target, priority: 0 })
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn matching(mut self, on: MatchOn) -> Self {
+        self.on = on;
+        self
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    //made with `glob` (the regex is the translated pattern) rather than `regex`
+    pub(crate) fn is_glob(&self) -> bool {
+        self.regex.as_str() == glob_to_regex(&self.pattern)
+    }
+}
+
+//serialized form of a MappingRule: the compiled regex is stored as text (a glob is saved
+//already translated) and compiled again on load
+#[derive(Serialize, Deserialize)]
+struct SavedMappingRule {
+    pattern: String,
+    regex: String,
+    on: MatchOn,
+    target: NodeId,
+    priority: i32,
+}
+
+impl From<MappingRule> for SavedMappingRule {
+    fn from(r: MappingRule) -> Self {
+        Self { regex: r.regex.as_str().to_string(), pattern: r.pattern, on: r.on, target: r.target, priority: r.priority }
+    }
+}
+
+impl TryFrom<SavedMappingRule> for MappingRule {
+    type Error = GraphError;
+
+    fn try_from(r: SavedMappingRule) -> Result<Self, GraphError> {
+        let regex = Regex::new(&r.regex).map_err(|e| GraphError::InvalidPattern {
+            pattern: r.pattern.clone(),
+            message: e.to_string(),
+        })?;
+        Ok(Self { pattern: r.pattern, regex, on: r.on, target: r.target, priority: r.priority })
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push_str(".*");
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out.push('$');
+    out
+}
+
+//result of evaluating the mapping rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingReport {
+    pub mapped: Vec<(NodeId, NodeId)>, //(impl, arch) entries that were added or changed
+    pub unmapped: Vec<NodeId>, //rule-derived entries no rule supports anymore
+    pub conflicts: Vec<GraphError>, //MappingAlreadyExists, one per disagreement
+}
+
 impl ReflexionGraph {
     //store/overwrite a mapping from implementation node to architecture node
     //1. impl_node must exist and must be in Implementation subgraph.
@@ -36,6 +151,7 @@ impl ReflexionGraph {
     pub fn set_mapping(&mut self, impl_node: NodeId, arch_node: NodeId) -> Result<(), GraphError> {
         self.expect_impl_node(impl_node)?;        
         self.expect_arch_node(arch_node)?;
+        self.check_kind_allowed(impl_node, arch_node)?;
 
         match self.maps_to.get(&impl_node).copied() {
             None => {
@@ -54,6 +170,40 @@ impl ReflexionGraph {
         Ok(self.maps_to.get(&impl_node).copied())
     }
 
+    //implicit (hierarchical) mapping: like get_arch_node, but an impl node without its own
+    //maps_to entry inherits the mapping of its nearest mapped ancestor in the containment tree.
+    //explicit entries on descendants always override what they would inherit.
+    pub fn get_effective_arch_node(&self, impl_node: NodeId) -> Result<Option<NodeId>, GraphError> {
+        self.expect_impl_node(impl_node)?;
+        Ok(self.effective_mapping(impl_node))
+    }
+
+    //unchecked version used by propagation
+    pub(crate) fn effective_mapping(&self, impl_node: NodeId) -> Option<NodeId> {
+        let mut current = Some(impl_node);
+        while let Some(n) = current {
+            if let Some(&arch) = self.maps_to.get(&n) {
+                return Some(arch);
+            }
+            current = self.nodes.get(&n).and_then(|node| node.parent);
+        }
+        None
+    }
+
+    //all impl nodes whose effective mapping is decided by impl_node's own maps_to entry:
+    //impl_node itself plus descendants, not descending below explicitly mapped ones
+    pub(crate) fn inheriting_nodes(&self, impl_node: NodeId) -> HashSet<NodeId> {
+        let mut out = HashSet::new();
+        let mut stack = vec![impl_node];
+        while let Some(n) = stack.pop() {
+            out.insert(n);
+            if let Some(node) = self.nodes.get(&n) {
+                stack.extend(node.children.iter().copied().filter(|c| !self.maps_to.contains_key(c)));
+            }
+        }
+        out
+    }
+
     pub fn is_mapped(&self, impl_node: NodeId) -> Result<bool, GraphError> {
         self.expect_impl_node(impl_node)?;
         Ok(self.maps_to.contains_key(&impl_node))
@@ -61,10 +211,12 @@ impl ReflexionGraph {
 
     pub fn remove_mapping(&mut self, impl_node: NodeId) -> Result<Option<NodeId>, GraphError> {
         self.expect_impl_node(impl_node)?;
+        self.rule_mapped.remove(&impl_node);
         Ok(self.maps_to.remove(&impl_node))
     }
 
     pub fn clear_mappings(&mut self) {
+        self.rule_mapped.clear();
         self.maps_to.clear()
     }
 
@@ -113,7 +265,9 @@ impl ReflexionGraph {
     ) -> Result<Option<NodeId>, GraphError> {
         self.validate_impl_node(impl_node)?;
         self.validate_arch_node(arch_node)?;
+        self.check_kind_allowed(impl_node, arch_node)?;
 
+        self.rule_mapped.remove(&impl_node); //explicit now, rules leave it alone
         Ok(self.maps_to.insert(impl_node, arch_node))
     }
 
@@ -126,6 +280,192 @@ impl ReflexionGraph {
     }
 }
 
+//rule engine: rules produce maps_to entries, remembered in rule_mapped so a later evaluation
+//can change or drop them. Hand-made mappings always win over rules (reported as conflicts).
+impl ReflexionGraph {
+    /// Incremental: register a mapping rule. Only the implementation nodes the new rule
+    /// matches can get another winning rule, so only they are re-evaluated. Conflicts end up
+    /// in `mapping_conflicts`.
+    pub fn add_mapping_rule(&mut self, rule: MappingRule) -> Result<RuleId, GraphError> {
+        self.expect_arch_node(rule.target)?;
+        let affected = self.nodes_matching_rule(&rule);
+        let id = self.next_mapping_rule_id;
+        self.next_mapping_rule_id += 1;
+        self.mapping_rules.insert(id, rule);
+        self.reapply_mapping_rules(&affected)?;
+        Ok(id)
+    }
+
+    /// Incremental: drop a mapping rule; nodes it matched fall back to the remaining rules.
+    pub fn remove_mapping_rule(&mut self, rule_id: RuleId) -> Result<MappingRule, GraphError> {
+        let rule = self.mapping_rules.remove(&rule_id).ok_or(GraphError::RuleNotFound(rule_id))?;
+        let affected = self.nodes_matching_rule(&rule);
+        self.reapply_mapping_rules(&affected)?;
+        Ok(rule)
+    }
+
+    pub fn mapping_rules(&self) -> impl Iterator<Item = (RuleId, &MappingRule)> + '_ {
+        self.mapping_rules.iter().map(|(&id, r)| (id, r))
+    }
+
+    //conflicts found by the last full evaluation plus those of nodes added since
+    pub fn mapping_conflicts(&self) -> &[GraphError] {
+        &self.mapping_conflicts
+    }
+
+    //the rule a maps_to entry came from (None = hand-made or unmapped)
+    pub fn mapping_rule_of(&self, impl_node: NodeId) -> Option<RuleId> {
+        self.rule_mapped.get(&impl_node).copied()
+    }
+
+    /// Incremental: evaluate every rule on every implementation node and update `maps_to`
+    /// (and the analysis) where the rule-derived mapping changed.
+    pub fn apply_mapping_rules(&mut self) -> Result<MappingReport, GraphError> {
+        let impl_nodes: Vec<NodeId> = self
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Implementation)
+            .map(|n| n.id)
+            .collect();
+
+        let report = self.apply_mapping_rules_to(&impl_nodes)?;
+        self.mapping_conflicts = report.conflicts.clone();
+        Ok(report)
+    }
+
+    //implementation nodes (in id order) a single rule matches, whatever the other rules say
+    fn nodes_matching_rule(&self, rule: &MappingRule) -> Vec<NodeId> {
+        self.nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Implementation)
+            .filter(|n| match rule.on {
+                MatchOn::Name => rule.is_match(&n.name),
+                MatchOn::Path => rule.is_match(&self.node_path(n.id)),
+            })
+            .map(|n| n.id)
+            .collect()
+    }
+
+    //re-evaluate `impl_nodes` after a rule change and replace their conflicts; the conflicts
+    //of other nodes are still valid
+    fn reapply_mapping_rules(&mut self, impl_nodes: &[NodeId]) -> Result<MappingReport, GraphError> {
+        let report = self.apply_mapping_rules_to(impl_nodes)?;
+        let affected: HashSet<NodeId> = impl_nodes.iter().copied().collect();
+        self.mapping_conflicts.retain(|c| {
+            !matches!(*c, GraphError::MappingAlreadyExists { impl_node, .. } if affected.contains(&impl_node))
+        });
+        self.mapping_conflicts.extend(report.conflicts.iter().cloned());
+        //stable, so the conflicts of one node keep their rule order
+        self.mapping_conflicts.sort_by_key(|c| match *c {
+            GraphError::MappingAlreadyExists { impl_node, .. } => impl_node,
+            _ => 0,
+        });
+        Ok(report)
+    }
+
+    //impl_nodes must be in id order, so reports and conflicts come out sorted
+    fn apply_mapping_rules_to(&mut self, impl_nodes: &[NodeId]) -> Result<MappingReport, GraphError> {
+        let mut report = MappingReport::default();
+        for &impl_node in impl_nodes {
+            let (winner, conflicts) = self.evaluate_mapping_rules(impl_node);
+            report.conflicts.extend(conflicts);
+
+            let current = self.maps_to.get(&impl_node).copied();
+            let from_rule = self.rule_mapped.contains_key(&impl_node);
+            match winner {
+                Some((_, arch)) if current.is_some() && !from_rule => {
+                    let old_arch = current.expect("checked above");
+                    if old_arch != arch {
+                        report.conflicts.push(GraphError::MappingAlreadyExists { impl_node, old_arch, new_arch: arch });
+                    }
+                }
+                Some((rule_id, arch)) => {
+                    if current != Some(arch) {
+                        self.set_mapping_overwrite_incremental(impl_node, arch)?;
+                        report.mapped.push((impl_node, arch));
+                    }
+                    self.rule_mapped.insert(impl_node, rule_id);
+                }
+                None if from_rule => {
+                    self.remove_mapping_incremental(impl_node)?;
+                    report.unmapped.push(impl_node);
+                }
+                None => {}
+            }
+        }
+        Ok(report)
+    }
+
+    //called by add_node: a fresh node has no edges yet, so setting maps_to is enough
+    pub(crate) fn map_new_node_by_rules(&mut self, impl_node: NodeId) {
+        let (winner, conflicts) = self.evaluate_mapping_rules(impl_node);
+        self.mapping_conflicts.extend(conflicts);
+        if let Some((rule_id, arch)) = winner {
+            self.maps_to.insert(impl_node, arch);
+            self.rule_mapped.insert(impl_node, rule_id);
+        }
+    }
+
+    //called when a node is removed: forget its rule mapping, rules targeting it and its conflicts
+    pub(crate) fn forget_mapping_node(&mut self, node: NodeId) {
+        self.rule_mapped.remove(&node);
+        self.mapping_rules.retain(|_, r| r.target != node);
+        self.mapping_conflicts.retain(|c| {
+            !matches!(*c, GraphError::MappingAlreadyExists { impl_node, old_arch, new_arch }
+                if impl_node == node || old_arch == node || new_arch == node)
+        });
+    }
+
+    //winning (rule, target) for a node plus the conflicts between equally ranked rules
+    fn evaluate_mapping_rules(&self, impl_node: NodeId) -> (Option<(RuleId, NodeId)>, Vec<GraphError>) {
+        if self.mapping_rules.is_empty() {
+            return (None, Vec::new());
+        }
+        let name = self.nodes.get(&impl_node).map(|n| n.name.as_str()).unwrap_or_default();
+        let path = self.node_path(impl_node);
+
+        //rules iterate by id, so a stable sort keeps the older rule first on equal priority.
+        //a rule whose target does not accept the node's kind does not apply to it.
+        let mut matching: Vec<(RuleId, &MappingRule)> = self
+            .mapping_rules
+            .iter()
+            .filter(|(_, r)| match r.on {
+                MatchOn::Name => r.is_match(name),
+                MatchOn::Path => r.is_match(&path),
+            })
+            .filter(|(_, r)| self.check_kind_allowed(impl_node, r.target).is_ok())
+            .map(|(&id, r)| (id, r))
+            .collect();
+        matching.sort_by_key(|&(_, r)| std::cmp::Reverse(r.priority));
+
+        let Some(&(winner_id, winner)) = matching.first() else {
+            return (None, Vec::new());
+        };
+        let conflicts = matching[1..]
+            .iter()
+            .filter(|(_, r)| r.priority == winner.priority && r.target != winner.target)
+            .map(|(_, r)| GraphError::MappingAlreadyExists {
+                impl_node,
+                old_arch: winner.target,
+                new_arch: r.target,
+            })
+            .collect();
+        (Some((winner_id, winner.target)), conflicts)
+    }
+
+    //names from the root of the containment tree down to `node`, joined with '/'
+    pub fn node_path(&self, node: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = self.nodes.get(&node);
+        while let Some(n) = current {
+            names.push(n.name.as_str());
+            current = n.parent.and_then(|p| self.nodes.get(&p));
+        }
+        names.reverse();
+        names.join("/")
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -165,8 +505,8 @@ mod tests {
         assert_eq!(g.get_arch_node(impl3).unwrap(), None);
 
         // mapped?
-        assert_eq!(g.is_mapped(impl1).unwrap(), true);
-        assert_eq!(g.is_mapped(impl3).unwrap(), false);
+        assert!(g.is_mapped(impl1).unwrap());
+        assert!(!g.is_mapped(impl3).unwrap());
     }
 
     #[test]
@@ -200,4 +540,161 @@ mod tests {
             other => panic!("unexpected error: {}", other),
         }
     }
+
+    #[test]
+    fn implicit_mapping_inherits_from_nearest_mapped_ancestor() {
+        let mut g = ReflexionGraph::new();
+
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+
+        // pkg > class > (method, query)
+        let pkg = g.add_node(Node::new("pkg", SubgraphKind::Implementation, None)).unwrap();
+        let class = g.add_node(Node::new("Class", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let method = g.add_node(Node::new("method", SubgraphKind::Implementation, Some(class))).unwrap();
+        let query = g.add_node(Node::new("query", SubgraphKind::Implementation, Some(class))).unwrap();
+
+        assert_eq!(g.get_effective_arch_node(method).unwrap(), None);
+
+        g.set_mapping(pkg, ui).unwrap();
+        g.set_mapping(query, db).unwrap();
+
+        // inherited through two levels
+        assert_eq!(g.get_effective_arch_node(class).unwrap(), Some(ui));
+        assert_eq!(g.get_effective_arch_node(method).unwrap(), Some(ui));
+        // explicit mapping on a descendant wins
+        assert_eq!(g.get_effective_arch_node(query).unwrap(), Some(db));
+        // get_arch_node stays the explicit lookup
+        assert_eq!(g.get_arch_node(method).unwrap(), None);
+
+        // query's subtree is not affected by pkg's entry
+        let inheriting = g.inheriting_nodes(pkg);
+        assert_eq!(inheriting, HashSet::from([pkg, class, method]));
+    }
+
+    #[test]
+    fn glob_rules_map_by_path_including_nodes_added_later() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+
+        let src = g.add_node(Node::new("src", SubgraphKind::Implementation, None)).unwrap();
+        let ui_pkg = g.add_node(Node::new("ui", SubgraphKind::Implementation, Some(src))).unwrap();
+        let login = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, Some(ui_pkg))).unwrap();
+        let db_pkg = g.add_node(Node::new("db", SubgraphKind::Implementation, Some(src))).unwrap();
+        assert_eq!(g.node_path(login), "src/ui/LoginPage");
+
+        let rule = g.add_mapping_rule(MappingRule::glob("src/ui/**", ui)).unwrap();
+        assert_eq!(g.get_arch_node(login).unwrap(), Some(ui));
+        assert_eq!(g.mapping_rule_of(login), Some(rule));
+        assert_eq!(g.get_arch_node(ui_pkg).unwrap(), None); //"src/ui" itself is not below src/ui/
+        assert_eq!(g.get_arch_node(db_pkg).unwrap(), None);
+
+        //new implementation nodes are mapped as they are added
+        let widgets = g.add_node(Node::new("widgets", SubgraphKind::Implementation, Some(ui_pkg))).unwrap();
+        let button = g.add_node(Node::new("Button", SubgraphKind::Implementation, Some(widgets))).unwrap();
+        assert_eq!(g.get_arch_node(button).unwrap(), Some(ui));
+
+        //dropping the rule drops what it produced
+        g.remove_mapping_rule(rule).unwrap();
+        assert_eq!(g.mapping_len(), 0);
+        assert_eq!(g.remove_mapping_rule(rule).unwrap_err(), GraphError::RuleNotFound(rule));
+    }
+
+    #[test]
+    fn rule_priority_and_conflicts() {
+        let mut g = ReflexionGraph::new();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+
+        let pkg = g.add_node(Node::new("service", SubgraphKind::Implementation, None)).unwrap();
+        let user_svc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let user_repo = g.add_node(Node::new("UserRepo", SubgraphKind::Implementation, Some(pkg))).unwrap();
+
+        g.add_mapping_rule(MappingRule::glob("service/*", service)).unwrap();
+        g.add_mapping_rule(MappingRule::regex("Repo$", db).unwrap().with_priority(1)).unwrap();
+        assert_eq!(g.get_arch_node(user_svc).unwrap(), Some(service));
+        assert_eq!(g.get_arch_node(user_repo).unwrap(), Some(db)); //higher priority wins
+        assert!(g.mapping_conflicts().is_empty());
+
+        //same priority, different target: the older rule wins, the clash is reported
+        g.add_mapping_rule(MappingRule::regex("^User", ui).unwrap()).unwrap();
+        assert_eq!(g.get_arch_node(user_svc).unwrap(), Some(service));
+        assert_eq!(
+            g.mapping_conflicts(),
+            &[GraphError::MappingAlreadyExists { impl_node: user_svc, old_arch: service, new_arch: ui }]
+        );
+
+        //hand-made mappings are never overwritten by rules
+        g.set_mapping_overwrite(user_repo, ui).unwrap();
+        let report = g.apply_mapping_rules().unwrap();
+        assert_eq!(g.get_arch_node(user_repo).unwrap(), Some(ui));
+        assert!(report.conflicts.contains(&GraphError::MappingAlreadyExists {
+            impl_node: user_repo,
+            old_arch: ui,
+            new_arch: db,
+        }));
+
+        assert!(matches!(MappingRule::regex("(", db), Err(GraphError::InvalidPattern { .. })));
+    }
+
+    #[test]
+    fn adding_a_rule_reevaluates_only_the_nodes_it_matches() {
+        use crate::core::forbidden::ForbiddenRule;
+
+        let mut g = ReflexionGraph::new();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let user_svc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+
+        //rule ids are counted per rule type
+        let forbidden = g.add_forbidden_rule(ForbiddenRule::new(ui, service)).unwrap();
+        let first = g.add_mapping_rule(MappingRule::regex("Service$", service).unwrap()).unwrap();
+        assert_eq!((forbidden, first), (1, 1));
+        g.add_mapping_rule(MappingRule::regex("^User", ui).unwrap()).unwrap();
+        let clash = GraphError::MappingAlreadyExists { impl_node: user_svc, old_arch: service, new_arch: ui };
+        assert_eq!(g.mapping_conflicts(), std::slice::from_ref(&clash));
+
+        //a rule that does not match UserService leaves its conflict alone
+        let page_rule = g.add_mapping_rule(MappingRule::regex("Page$", ui).unwrap()).unwrap();
+        assert_eq!(g.get_arch_node(page).unwrap(), Some(ui));
+        assert_eq!(g.mapping_conflicts(), std::slice::from_ref(&clash));
+
+        //and the incremental result is what a full evaluation gives
+        let incremental = (g.iter_mapping().collect::<HashSet<_>>(), g.mapping_conflicts().to_vec());
+        g.apply_mapping_rules().unwrap();
+        assert_eq!(incremental, (g.iter_mapping().collect::<HashSet<_>>(), g.mapping_conflicts().to_vec()));
+
+        g.remove_mapping_rule(first).unwrap();
+        assert_eq!(g.get_arch_node(user_svc).unwrap(), Some(ui));
+        assert!(g.mapping_conflicts().is_empty());
+        g.remove_mapping_rule(page_rule).unwrap();
+        assert_eq!(g.get_arch_node(page).unwrap(), None);
+    }
+
+    #[test]
+    fn mapping_rules_update_the_analysis_incrementally() {
+        use crate::core::types::EdgeKind;
+        use crate::core::state::EdgeState;
+
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let arch = g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+
+        let login = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_impl_edge(login, usersvc, EdgeKind::calls()).unwrap();
+        assert!(matches!(g.edges.get(&e).unwrap().state, EdgeState::Unmapped));
+
+        g.add_mapping_rule(MappingRule::regex("Page$", ui).unwrap()).unwrap();
+        g.add_mapping_rule(MappingRule::regex("Service$", service).unwrap()).unwrap();
+        assert!(matches!(g.edges.get(&e).unwrap().state, EdgeState::Convergent));
+        assert_eq!(g.edges.get(&arch).unwrap().counter, 1);
+
+        g.run_from_scratch().unwrap();
+        assert!(matches!(g.edges.get(&e).unwrap().state, EdgeState::Convergent));
+        assert_eq!(g.edges.get(&arch).unwrap().counter, 1);
+    }
 }
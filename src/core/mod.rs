@@ -1,4 +1,6 @@
 pub mod types;
+pub mod interner;
+pub mod dense;
 pub mod state;
 pub mod graph;
 pub mod mapping;
@@ -6,3 +8,15 @@ pub mod propagate;
 pub mod lifting;
 pub mod classify;
 pub mod delta;
+pub mod parallel;
+pub mod forbidden;
+pub mod layers;
+pub mod recommend;
+pub mod kinds;
+pub mod attrs;
+pub mod builder;
+pub mod explain;
+pub mod snapshot;
+pub mod model;
+pub mod toon;
+pub mod json;
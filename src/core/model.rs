@@ -0,0 +1,497 @@
+// exchange document for the inputs (and optionally the results) of a reflexion analysis
+//
+// Format-neutral: toon.rs reads and writes it as TOON, json.rs as JSON (schema in
+// docs/model.schema.json). Every table is a list of flat rows with
+// the same fields, so encoders that support tabular arrays can write them compactly.
+//  - architecture / implementation: nodes (id, name, parent, kind) and edges (from, to, kind),
+//    both with optional attributes; the architecture section also lists allowed edges
+//  - mapping: explicit maps_to entries (implicit ones follow from the node hierarchy, rule
+//    based ones from mapping_rules)
+//  - edge_kinds, kind_restrictions, mapping_rules, forbidden, layering: everything else the
+//    classification depends on, so importing an export gives the same analysis
+//  - results (export only): every edge with its state and counter
+// Node ids are the document's own: one namespace for both sections, parents listed before
+// their children. Importing assigns fresh graph ids and reports the translation.
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::core::forbidden::ForbiddenRule;
+use crate::core::graph::{GraphError, Node, ReflexionGraph};
+use crate::core::layers::{Layering, LayeringMode};
+use crate::core::mapping::{MappingRule, MatchOn};
+use crate::core::state::EdgeState;
+use crate::core::types::{Attributes, Counter, EdgeId, EdgeKind, KindId, NodeId, NodeKind, SubgraphKind};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelDocument {
+    #[serde(default)]
+    pub architecture: SubgraphDocument,
+    #[serde(default)]
+    pub implementation: SubgraphDocument,
+    #[serde(default)]
+    pub mapping: Vec<MappingRow>,
+    #[serde(default)]
+    pub edge_kinds: Vec<EdgeKindRow>,
+    #[serde(default)]
+    pub kind_restrictions: Vec<KindRestrictionRow>,
+    #[serde(default)]
+    pub mapping_rules: Vec<MappingRuleRow>,
+    #[serde(default)]
+    pub forbidden: Vec<ForbiddenRow>,
+    #[serde(default)]
+    pub layering: Option<LayeringDocument>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<ResultRow>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubgraphDocument {
+    #[serde(default)]
+    pub nodes: Vec<NodeRow>,
+    #[serde(default)]
+    pub edges: Vec<EdgeRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<EdgeRow>, //architecture only: permitted but not required
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeRow {
+    pub id: NodeId,
+    pub name: String,
+    pub parent: Option<NodeId>,
+    #[serde(default)]
+    pub kind: String, //NodeKind name, e.g. "ClassNode"; unknown names become custom kinds, "" = generic
+    #[serde(default, skip_serializing_if = "Attributes::is_empty", with = "plain_attrs")]
+    pub attrs: Attributes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeRow {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty", with = "plain_attrs")]
+    pub attrs: Attributes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MappingRow {
+    pub implementation: NodeId,
+    pub architecture: NodeId,
+}
+
+//kind <: parent, e.g. calls <: depends_on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeKindRow {
+    pub kind: String,
+    pub parent: String,
+}
+
+//only implementation nodes of these kinds may be mapped to `architecture`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KindRestrictionRow {
+    pub architecture: NodeId,
+    pub kinds: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternSyntax {
+    Glob,  //MappingRule::glob
+    Regex, //MappingRule::regex
+}
+
+//listed by rule id: on equal priority the earlier row wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MappingRuleRow {
+    pub pattern: String,
+    pub syntax: PatternSyntax,
+    pub on: MatchOn,
+    pub target: NodeId,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForbiddenRow {
+    pub from: Option<NodeId>, //None = any architecture node
+    pub to: Option<NodeId>,   //None = any architecture node
+    #[serde(default)]
+    pub kind: String, //"" = any edge kind
+}
+
+//the allowed edges derived from the layering are not listed under architecture.allowed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayeringDocument {
+    pub mode: LayeringMode,
+    pub kind: String,
+    pub layers: Vec<Vec<NodeId>>, //top to bottom
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultRow {
+    pub edge: EdgeId,
+    pub subgraph: SubgraphKind,
+    pub from: NodeId,
+    pub to: NodeId,
+    pub kind: String,
+    pub state: EdgeState,
+    pub counter: Counter,
+}
+
+//an imported, analysed graph and where the document's node ids ended up
+pub struct ModelImport {
+    pub graph: ReflexionGraph,
+    pub node_ids: BTreeMap<NodeId, NodeId>, //document id -> graph id
+}
+
+impl ReflexionGraph {
+    /// Export nodes, edges, explicit mappings and every rule the analysis depends on (ordered
+    /// by id); with `with_results` also every edge's state and counter. Node ids in the
+    /// document are the graph's ids.
+    pub fn to_model(&self, with_results: bool) -> ModelDocument {
+        let mut doc = ModelDocument::default();
+        for n in self.nodes.values() {
+            let row = NodeRow { id: n.id, name: n.name.clone(), parent: n.parent, kind: n.kind.to_string(), attrs: n.attrs.clone() };
+            match n.subgraph {
+                SubgraphKind::Architecture => doc.architecture.nodes.push(row),
+                SubgraphKind::Implementation => doc.implementation.nodes.push(row),
+                SubgraphKind::Propagated => {}
+            }
+        }
+        for e in self.edges.values() {
+            let row = EdgeRow { from: e.from, to: e.to, kind: self.resolve_kind(e.kind).to_string(), attrs: e.attrs.clone() };
+            match e.subgraph {
+                //derived again from the layering on import
+                SubgraphKind::Architecture if self.layer_edges.contains(&e.id) => {}
+                SubgraphKind::Architecture if self.allowed_edges.contains(&e.id) => doc.architecture.allowed.push(row),
+                SubgraphKind::Architecture => doc.architecture.edges.push(row),
+                SubgraphKind::Implementation => doc.implementation.edges.push(row),
+                SubgraphKind::Propagated => {}
+            }
+        }
+
+        //entries made by mapping rules follow from the rules
+        let mut mapping: Vec<MappingRow> = self
+            .maps_to
+            .iter()
+            .filter(|&(impl_node, _)| !self.rule_mapped.contains_key(impl_node))
+            .map(|(&implementation, &architecture)| MappingRow { implementation, architecture })
+            .collect();
+        mapping.sort_by_key(|m| m.implementation);
+        doc.mapping = mapping;
+
+        //the layering's own calls <: depends_on is registered again by set_layering
+        let mut edge_kinds: Vec<EdgeKindRow> = self
+            .kind_parents
+            .iter()
+            .filter(|&(&kind, _)| !(self.layer_subtype && kind == KindId::CALLS))
+            .map(|(&kind, &parent)| EdgeKindRow {
+                kind: self.resolve_kind(kind).to_string(),
+                parent: self.resolve_kind(parent).to_string(),
+            })
+            .collect();
+        edge_kinds.sort_by(|a, b| a.kind.cmp(&b.kind));
+        doc.edge_kinds = edge_kinds;
+
+        let mut kind_restrictions: Vec<KindRestrictionRow> = self
+            .kind_restrictions
+            .iter()
+            .map(|(&architecture, kinds)| {
+                let mut kinds: Vec<String> = kinds.iter().map(|k| k.to_string()).collect();
+                kinds.sort();
+                KindRestrictionRow { architecture, kinds }
+            })
+            .collect();
+        kind_restrictions.sort_by_key(|r| r.architecture);
+        doc.kind_restrictions = kind_restrictions;
+
+        doc.mapping_rules = self
+            .mapping_rules
+            .values()
+            .map(|r| MappingRuleRow {
+                pattern: r.pattern.clone(),
+                syntax: if r.is_glob() { PatternSyntax::Glob } else { PatternSyntax::Regex },
+                on: r.on,
+                target: r.target,
+                priority: r.priority,
+            })
+            .collect();
+        doc.forbidden = self
+            .forbidden_rules
+            .values()
+            .map(|r| ForbiddenRow {
+                from: r.from,
+                to: r.to,
+                kind: r.kind.as_ref().map(|k| k.to_string()).unwrap_or_default(),
+            })
+            .collect();
+        doc.layering = self.layering.as_ref().map(|l| LayeringDocument {
+            mode: l.mode,
+            kind: l.kind.to_string(),
+            layers: l.layers.clone(),
+        });
+
+        if with_results {
+            doc.results = Some(
+                self.edges
+                    .values()
+                    .map(|e| ResultRow {
+                        edge: e.id,
+                        subgraph: e.subgraph,
+                        from: e.from,
+                        to: e.to,
+                        kind: self.resolve_kind(e.kind).to_string(),
+                        state: e.state,
+                        counter: e.counter,
+                    })
+                    .collect(),
+            );
+        }
+        doc
+    }
+
+    /// Build and analyse a graph from a document. `results` are ignored: the analysis is
+    /// recomputed from the inputs. Errors are wrapped in `GraphError::ModelRow` naming the
+    /// offending row (e.g. `implementation.edges[3]`); unknown node ids inside are reported as
+    /// NodeNotFound/ParentNotFound with the document's id.
+    pub fn from_model(doc: &ModelDocument) -> Result<ModelImport, GraphError> {
+        let mut g = ReflexionGraph::new();
+        let mut ids: BTreeMap<NodeId, NodeId> = BTreeMap::new();
+
+        for (name, subgraph, section) in [
+            ("architecture", SubgraphKind::Architecture, &doc.architecture),
+            ("implementation", SubgraphKind::Implementation, &doc.implementation),
+        ] {
+            for (i, row) in section.nodes.iter().enumerate() {
+                let id = add_node_row(&mut g, &ids, subgraph, row).map_err(in_row(|| format!("{}.nodes[{}]", name, i)))?;
+                ids.insert(row.id, id);
+            }
+        }
+        let node = |id: NodeId| ids.get(&id).copied().ok_or(GraphError::NodeNotFound(id));
+        let node_opt = |id: Option<NodeId>| id.map(node).transpose();
+
+        //the kind hierarchy and the restrictions first: they decide how the rest is classified
+        for (i, k) in doc.edge_kinds.iter().enumerate() {
+            g.add_edge_kind_subtype(EdgeKind::from(k.kind.as_str()), EdgeKind::from(k.parent.as_str()))
+                .map_err(in_row(|| format!("edge_kinds[{}]", i)))?;
+        }
+        for (i, r) in doc.kind_restrictions.iter().enumerate() {
+            (|| g.restrict_mapping_kinds(node(r.architecture)?, r.kinds.iter().map(|k| NodeKind::from(k.as_str()))))()
+                .map_err(in_row(|| format!("kind_restrictions[{}]", i)))?;
+        }
+
+        //mappings before edges, so every edge is propagated once
+        for (i, m) in doc.mapping.iter().enumerate() {
+            (|| g.set_mapping(node(m.implementation)?, node(m.architecture)?))()
+                .map_err(in_row(|| format!("mapping[{}]", i)))?;
+        }
+        for (i, r) in doc.mapping_rules.iter().enumerate() {
+            (|| {
+                let target = node(r.target)?;
+                let rule = match r.syntax {
+                    PatternSyntax::Glob => MappingRule::glob(r.pattern.as_str(), target),
+                    PatternSyntax::Regex => MappingRule::regex(r.pattern.as_str(), target)?,
+                };
+                g.add_mapping_rule(rule.matching(r.on).with_priority(r.priority))
+            })()
+            .map_err(in_row(|| format!("mapping_rules[{}]", i)))?;
+        }
+
+        for (i, e) in doc.architecture.edges.iter().enumerate() {
+            add_edge_row(&mut g, &ids, e, ReflexionGraph::add_arch_edge).map_err(in_row(|| format!("architecture.edges[{}]", i)))?;
+        }
+        for (i, e) in doc.architecture.allowed.iter().enumerate() {
+            add_edge_row(&mut g, &ids, e, ReflexionGraph::add_allowed_edge).map_err(in_row(|| format!("architecture.allowed[{}]", i)))?;
+        }
+        for (i, r) in doc.forbidden.iter().enumerate() {
+            (|| {
+                let kind = (!r.kind.is_empty()).then(|| EdgeKind::from(r.kind.as_str()));
+                g.add_forbidden_rule(ForbiddenRule { from: node_opt(r.from)?, to: node_opt(r.to)?, kind })
+            })()
+            .map_err(in_row(|| format!("forbidden[{}]", i)))?;
+        }
+        if let Some(l) = &doc.layering {
+            (|| {
+                let layers = l
+                    .layers
+                    .iter()
+                    .map(|layer| layer.iter().map(|&n| node(n)).collect::<Result<Vec<_>, _>>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                g.set_layering(Layering { mode: l.mode, layers, kind: EdgeKind::from(l.kind.as_str()) })
+            })()
+            .map_err(in_row(|| "layering".to_string()))?;
+        }
+
+        for (i, e) in doc.implementation.edges.iter().enumerate() {
+            add_edge_row(&mut g, &ids, e, ReflexionGraph::add_impl_edge).map_err(in_row(|| format!("implementation.edges[{}]", i)))?;
+        }
+
+        Ok(ModelImport { graph: g, node_ids: ids })
+    }
+}
+
+//one node row of a section; `ids` holds the document ids imported so far
+fn add_node_row(
+    g: &mut ReflexionGraph,
+    ids: &BTreeMap<NodeId, NodeId>,
+    subgraph: SubgraphKind,
+    row: &NodeRow,
+) -> Result<NodeId, GraphError> {
+    if ids.contains_key(&row.id) {
+        return Err(GraphError::InvalidDocument(format!("duplicate node id {}", row.id)));
+    }
+    let parent = match row.parent {
+        Some(p) => {
+            let parent = *ids.get(&p).ok_or(GraphError::ParentNotFound(p))?;
+            let found = g.nodes[&parent].subgraph;
+            if found != subgraph {
+                return Err(GraphError::WrongSubgraph { node: p, expected: subgraph, found });
+            }
+            Some(parent)
+        }
+        None => None,
+    };
+    let mut node = Node::new(row.name.clone(), subgraph, parent);
+    if !row.kind.is_empty() {
+        node = node.with_kind(NodeKind::from(row.kind.as_str()));
+    }
+    node.attrs = row.attrs.clone();
+    g.add_node(node)
+}
+
+//one edge row, added through `add` (add_arch_edge, add_allowed_edge or add_impl_edge)
+fn add_edge_row(
+    g: &mut ReflexionGraph,
+    ids: &BTreeMap<NodeId, NodeId>,
+    row: &EdgeRow,
+    add: fn(&mut ReflexionGraph, NodeId, NodeId, EdgeKind) -> Result<EdgeId, GraphError>,
+) -> Result<(), GraphError> {
+    let node = |id: NodeId| ids.get(&id).copied().ok_or(GraphError::NodeNotFound(id));
+    let id = add(g, node(row.from)?, node(row.to)?, EdgeKind::from(row.kind.as_str()))?;
+    g.edges.get_mut(&id).expect("just added").attrs = row.attrs.clone();
+    Ok(())
+}
+
+//wrap an error with the document row it came from (the path is only built on error)
+fn in_row(row: impl FnOnce() -> String) -> impl FnOnce(GraphError) -> GraphError {
+    move |error| GraphError::ModelRow { row: row(), error: Box::new(error) }
+}
+
+//attributes as plain scalars ({"line": 12}) rather than AttrValue's tagged form, which is
+//what extractors write naturally
+mod plain_attrs {
+    use std::collections::BTreeMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::core::types::{AttrValue, Attributes};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Plain {
+        Bool(bool),
+        Int(i64),
+        Float(f64),
+        Str(String),
+    }
+
+    pub fn serialize<S: Serializer>(attrs: &Attributes, s: S) -> Result<S::Ok, S::Error> {
+        let plain: BTreeMap<&str, Plain> = attrs
+            .iter()
+            .map(|(k, v)| {
+                let v = match v {
+                    AttrValue::Bool(b) => Plain::Bool(*b),
+                    AttrValue::Int(i) => Plain::Int(*i),
+                    AttrValue::Float(f) => Plain::Float(*f),
+                    AttrValue::Str(s) => Plain::Str(s.clone()),
+                };
+                (k.as_str(), v)
+            })
+            .collect();
+        plain.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Attributes, D::Error> {
+        let plain = BTreeMap::<String, Plain>::deserialize(d)?;
+        Ok(plain
+            .into_iter()
+            .map(|(k, v)| {
+                let v = match v {
+                    Plain::Bool(b) => AttrValue::Bool(b),
+                    Plain::Int(i) => AttrValue::Int(i),
+                    Plain::Float(f) => AttrValue::Float(f),
+                    Plain::Str(s) => AttrValue::Str(s),
+                };
+                (k, v)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{GraphError, ReflexionGraph};
+    use crate::core::types::NodeKind;
+    use super::{EdgeRow, MappingRow, ModelDocument, NodeRow};
+
+    fn row(id: u32, name: &str, parent: Option<u32>, kind: &str) -> NodeRow {
+        NodeRow { id, name: name.to_string(), parent, kind: kind.to_string(), attrs: Default::default() }
+    }
+
+    fn edge(from: u32, to: u32, kind: &str) -> EdgeRow {
+        EdgeRow { from, to, kind: kind.to_string(), attrs: Default::default() }
+    }
+
+    #[test]
+    fn import_translates_ids_and_rejects_bad_references() {
+        let mut doc = ModelDocument::default();
+        doc.architecture.nodes = vec![row(100, "UI", None, "UINode"), row(101, "DB", None, "Store")];
+        doc.architecture.edges = vec![edge(100, 101, "calls")];
+        doc.implementation.nodes = vec![
+            row(7, "src", None, "PackageNode"),
+            row(8, "Page", Some(7), "ClassNode"),
+            row(9, "Repo", None, "ClassNode"),
+        ];
+        doc.mapping = vec![
+            MappingRow { implementation: 7, architecture: 100 },
+            MappingRow { implementation: 9, architecture: 101 },
+        ];
+        doc.implementation.edges = vec![edge(8, 9, "calls")];
+
+        let imported = ReflexionGraph::from_model(&doc).unwrap();
+        let page = imported.node_ids[&8];
+        assert_eq!(imported.graph.node_kind(page).unwrap(), &NodeKind::ClassNode);
+        assert_eq!(imported.graph.node_kind(imported.node_ids[&101]).unwrap(), &NodeKind::custom("Store"));
+        assert_eq!(imported.graph.get_effective_arch_node(page).unwrap(), Some(imported.node_ids[&100]));
+        assert_eq!(imported.graph.count_violations(), 0); //Page inherits UI, UI -> DB is specified
+
+        //every model error names its row
+        let row_error = |doc: &ModelDocument| match ReflexionGraph::from_model(doc) {
+            Err(GraphError::ModelRow { row, error }) => (row, *error),
+            other => panic!("expected a model row error, got {:?}", other.map(|_| ())),
+        };
+
+        let mut dup = doc.clone();
+        dup.implementation.nodes.push(row(100, "Other", None, "ClassNode"));
+        let (at, error) = row_error(&dup);
+        assert_eq!(at, "implementation.nodes[3]");
+        assert!(matches!(error, GraphError::InvalidDocument(_)));
+
+        let mut dangling = doc.clone();
+        dangling.implementation.edges.push(edge(8, 42, "calls"));
+        assert_eq!(row_error(&dangling), ("implementation.edges[1]".to_string(), GraphError::NodeNotFound(42)));
+
+        let mut orphan = doc.clone();
+        orphan.implementation.nodes.push(row(10, "Odd", Some(55), "ClassNode"));
+        assert_eq!(row_error(&orphan), ("implementation.nodes[3]".to_string(), GraphError::ParentNotFound(55)));
+
+        let mut swapped = doc.clone();
+        swapped.mapping.push(MappingRow { implementation: 100, architecture: 101 });
+        let (at, error) = row_error(&swapped);
+        assert_eq!(at, "mapping[2]");
+        assert!(matches!(error, GraphError::WrongSubgraph { .. }));
+
+        let mut cross = doc;
+        cross.implementation.nodes.push(row(10, "Odd", Some(100), "ClassNode"));
+        let (at, error) = row_error(&cross);
+        assert_eq!(at, "implementation.nodes[3]");
+        assert!(matches!(error, GraphError::WrongSubgraph { node: 100, .. }));
+    }
+}
@@ -0,0 +1,246 @@
+// parallel from-scratch analysis
+//
+// Same result as run_from_scratch, computed in three phases:
+// 1) implementation edges (in id order) are split into contiguous chunks; each thread maps its
+//    edges' endpoints and groups them by propagated (from, to, propagation kind), in first-seen
+//    order
+// 2) the chunk groups are merged in chunk order, so every group keeps its first-seen position
+//    and its impl edges stay sorted; each distinct group is then checked against the forbidden
+//    rules and lifted, again in parallel (both only read the graph)
+// 3) the merged groups are applied on one thread, in that order. Propagated edges are therefore
+//    created in the same order, and get the same ids, as in the sequential run.
+use std::collections::HashMap;
+use std::thread;
+use crate::core::graph::{EdgeKey, GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::types::{Counter, EdgeId, RuleId, SubgraphKind};
+
+//impl edges behind one propagated edge, in first-seen order
+type Groups = Vec<(EdgeKey, Vec<EdgeId>)>;
+
+//what lifting decides for a whole propagated edge
+enum Resolution {
+    Forbidden(RuleId),
+    Lifted(EdgeId),
+    Divergent,
+}
+
+impl ReflexionGraph {
+    /// `run_from_scratch` on `threads` worker threads (0 = one per available core).
+    /// The resulting graph (edge ids, states, counters and bookkeeping) is identical to the
+    /// sequential run.
+    pub fn run_from_scratch_parallel(&mut self, threads: usize) -> Result<(), GraphError> {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        self.recycle_propagated_edges();
+        let result = self.analyse_all_edges_parallel(threads);
+        self.free_propagated_ids.clear(); //see run_from_scratch
+        result
+    }
+
+    fn analyse_all_edges_parallel(&mut self, threads: usize) -> Result<(), GraphError> {
+        self.init_states();
+
+        let impl_edge_ids: Vec<EdgeId> = self
+            .edges
+            .iter()
+            .filter(|(_, e)| e.subgraph == SubgraphKind::Implementation)
+            .map(|(eid, _)| eid)
+            .collect();
+
+        //1) map + group per chunk
+        let graph = &*self;
+        let chunks: Vec<(Groups, Vec<EdgeId>)> = thread::scope(|s| {
+            let handles: Vec<_> = impl_edge_ids
+                .chunks(chunk_len(impl_edge_ids.len(), threads))
+                .map(|chunk| s.spawn(move || graph.group_impl_edges(chunk)))
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("propagation worker panicked")).collect()
+        });
+
+        //2) deterministic merge, then resolve every group
+        let mut groups: Groups = Vec::new();
+        let mut position: HashMap<EdgeKey, usize> = HashMap::new();
+        let mut unmapped: Vec<EdgeId> = Vec::new();
+        for (chunk_groups, chunk_unmapped) in chunks {
+            for (key, impls) in chunk_groups {
+                match position.get(&key) {
+                    Some(&i) => groups[i].1.extend(impls),
+                    None => {
+                        position.insert(key, groups.len());
+                        groups.push((key, impls));
+                    }
+                }
+            }
+            unmapped.extend(chunk_unmapped);
+        }
+
+        let resolutions: Vec<Resolution> = thread::scope(|s| {
+            let handles: Vec<_> = groups
+                .chunks(chunk_len(groups.len(), threads))
+                .map(|chunk| {
+                    s.spawn(move || chunk.iter().map(|(key, _)| graph.resolve_group(*key)).collect::<Result<Vec<_>, _>>())
+                })
+                .collect();
+            let mut out = Vec::with_capacity(groups.len());
+            for h in handles {
+                out.extend(h.join().expect("lifting worker panicked")?);
+            }
+            Ok::<_, GraphError>(out)
+        })?;
+
+        //3) apply
+        for eid in unmapped {
+            if let Some(e) = self.edges.get_mut(&eid) {
+                e.state = EdgeState::Unmapped;
+            }
+        }
+        for ((key, impls), resolution) in groups.into_iter().zip(resolutions) {
+            self.apply_group(key, impls, resolution)?;
+        }
+
+        self.finalize_architecture_states();
+        Ok(())
+    }
+
+    //phase 1 for one chunk: (groups, unmapped impl edges)
+    fn group_impl_edges(&self, chunk: &[EdgeId]) -> (Groups, Vec<EdgeId>) {
+        let mut groups: Groups = Vec::new();
+        let mut position: HashMap<EdgeKey, usize> = HashMap::new();
+        let mut unmapped = Vec::new();
+
+        for &eid in chunk {
+            let e = &self.edges[&eid];
+            let (Some(from_arch), Some(to_arch)) = (self.effective_mapping(e.from), self.effective_mapping(e.to)) else {
+                unmapped.push(eid);
+                continue;
+            };
+            let key = (from_arch, to_arch, self.propagation_kind(e.kind));
+            match position.get(&key) {
+                Some(&i) => groups[i].1.push(eid),
+                None => {
+                    position.insert(key, groups.len());
+                    groups.push((key, vec![eid]));
+                }
+            }
+        }
+        (groups, unmapped)
+    }
+
+    //forbidden rules first, then lifting (same order as propagate_and_lift)
+    fn resolve_group(&self, (from, to, kind): EdgeKey) -> Result<Resolution, GraphError> {
+        if let Some(rule) = self.forbidding_rule_id(from, to, kind) {
+            return Ok(Resolution::Forbidden(rule));
+        }
+        Ok(match self.lift_id(from, to, kind)? {
+            Some(arch_eid) => Resolution::Lifted(arch_eid),
+            None => Resolution::Divergent,
+        })
+    }
+
+    fn apply_group(&mut self, (from, to, kind): EdgeKey, impls: Vec<EdgeId>, resolution: Resolution) -> Result<(), GraphError> {
+        let prop_id = self.get_or_create_propagated_edge_id(from, to, kind)?;
+        let count = impls.len() as Counter;
+        if let Some(pe) = self.edges.get_mut(&prop_id) {
+            pe.counter += count;
+        }
+        for &ie in &impls {
+            self.impl_to_prop.insert(ie, prop_id);
+        }
+        self.propagation_table.entry(prop_id).or_default().extend(impls);
+
+        let state = match resolution {
+            Resolution::Forbidden(rule) => {
+                self.forbidden_by.insert(prop_id, rule);
+                EdgeState::Forbidden
+            }
+            Resolution::Lifted(arch_eid) => {
+                self.record_lift(prop_id, arch_eid);
+                let state = self.implemented_state(arch_eid);
+                if let Some(ae) = self.edges.get_mut(&arch_eid) {
+                    ae.counter += count;
+                    ae.state = state;
+                }
+                state
+            }
+            Resolution::Divergent => EdgeState::Divergent,
+        };
+        self.set_propagated_state(prop_id, state);
+        Ok(())
+    }
+}
+
+//at least one item per chunk, at most `threads` chunks
+fn chunk_len(items: usize, threads: usize) -> usize {
+    items.div_ceil(threads.max(1)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::forbidden::ForbiddenRule;
+    use crate::core::graph::{Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    //hierarchy, implicit mappings, allowed edges, a forbidden rule, subkinds and unmapped nodes
+    fn build() -> ReflexionGraph {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, Some(app))).unwrap();
+        let svc = g.add_node(Node::new("Service", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::calls(), EdgeKind::depends_on()).unwrap();
+        g.add_arch_edge(ui, svc, EdgeKind::calls()).unwrap();
+        g.add_arch_edge(app, db, EdgeKind::depends_on()).unwrap();
+        g.add_allowed_edge(svc, ui, EdgeKind::from("imports")).unwrap();
+        g.add_forbidden_rule(ForbiddenRule::new(ui, db)).unwrap();
+
+        let arch = [ui, svc, db];
+        let mut impls = Vec::new();
+        for i in 0..30 {
+            let pkg = g.add_node(Node::new(format!("pkg{i}"), SubgraphKind::Implementation, None)).unwrap();
+            if i % 7 != 6 {
+                g.set_mapping(pkg, arch[i % 3]).unwrap();
+            }
+            for j in 0..3 {
+                impls.push(g.add_node(Node::new(format!("f{i}_{j}"), SubgraphKind::Implementation, Some(pkg))).unwrap());
+            }
+        }
+        let kinds = [EdgeKind::calls(), EdgeKind::from("imports"), EdgeKind::depends_on()];
+        for (n, &from) in impls.iter().enumerate() {
+            for step in [1, 5, 17] {
+                let to = impls[(n * 31 + step) % impls.len()];
+                g.add_impl_edge(from, to, kinds[(n + step) % 3].clone()).unwrap();
+            }
+        }
+        g
+    }
+
+    #[test]
+    fn parallel_run_matches_the_sequential_run_exactly() {
+        let mut seq = build();
+        seq.run_from_scratch().unwrap();
+
+        for threads in [1, 3, 8, 0] {
+            let mut par = build();
+            par.run_from_scratch_parallel(threads).unwrap();
+            assert!(par.edges().eq(seq.edges()));
+            assert_eq!(par.propagation_table, seq.propagation_table);
+            assert_eq!(par.impl_to_prop, seq.impl_to_prop);
+            assert_eq!(par.lifted_to, seq.lifted_to);
+            assert_eq!(par.lifted_from, seq.lifted_from);
+            assert_eq!(par.forbidden_by, seq.forbidden_by);
+            assert_eq!(par.prop_index, seq.prop_index);
+        }
+        assert!(seq.count_violations() > 0 && seq.count_forbidden() > 0);
+
+        //a second parallel run over an analysed graph gives the same result again
+        let mut again = build();
+        again.run_from_scratch().unwrap();
+        again.run_from_scratch_parallel(4).unwrap();
+        seq.run_from_scratch().unwrap();
+        assert!(again.edges().eq(seq.edges()));
+    }
+}
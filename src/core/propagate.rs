@@ -1,32 +1,39 @@
-use std::collections::HashSet;
-
 use crate::core::graph::{GraphError, ReflexionGraph, Edge};
 use crate::core::state::EdgeState;
-use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind};
+use crate::core::types::{EdgeId, NodeId, SubgraphKind, EdgeKind, KindId};
 
 impl ReflexionGraph {
     /// Reuse an existing propagated edge if present, otherwise create it.
     ///
     /// Propagated edges live in architecture-space adjacency (`arch_out`) but are marked
     /// with `subgraph = Propagated`.
+    ///
+    /// Reuse respects the edge-kind registry: `kind` is replaced by its propagation kind, the
+    /// most specific supertype-or-self the architecture (or a forbidden rule) refers to. With
+    /// `calls`, `imports` <: `depends_on` and only `depends_on` edges specified, both kinds
+    /// reuse one propagated `depends_on` edge; a specified `calls` edge keeps `calls` apart.
     pub fn get_or_create_propagated_edge(
         &mut self,
         from_arch: NodeId,
         to_arch: NodeId,
         kind: EdgeKind,
     ) -> Result<EdgeId, GraphError> {
-        // Reuse if exists: scan outgoing edges in arch_out[from_arch]
-        if let Some(out) = self.arch_out.get(&from_arch) {
-            for &eid in out {
-                let e = self
-                    .edges
-                    .get(&eid)
-                    .ok_or(GraphError::EdgeNotFound(eid))?;
-
-                if e.subgraph == SubgraphKind::Propagated && e.to == to_arch && e.kind == kind {
-                    return Ok(eid);
-                }
-            }
+        let kind = self.kinds.intern(&kind);
+        let kind = self.propagation_kind(kind);
+        self.get_or_create_propagated_edge_id(from_arch, to_arch, kind)
+    }
+
+    //allocation-free variant used by propagation (only a new propagated edge allocates);
+    //`kind` is taken as is, callers pass the propagation kind
+    pub(crate) fn get_or_create_propagated_edge_id(
+        &mut self,
+        from_arch: NodeId,
+        to_arch: NodeId,
+        kind: KindId,
+    ) -> Result<EdgeId, GraphError> {
+        // Reuse if exists: O(1) lookup in prop_index
+        if let Some(&eid) = self.prop_index.get(&(from_arch, to_arch, kind)) {
+            return Ok(eid);
         }
 
         // Else create new propagated edge
@@ -38,6 +45,7 @@ impl ReflexionGraph {
             subgraph: SubgraphKind::Propagated,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         };
 
         self.add_edge(new_edge)
@@ -47,9 +55,10 @@ impl ReflexionGraph {
     ///
     /// Steps:
     /// 1) Read (from_impl, to_impl, kind)
-    /// 2) Map endpoints using `maps_to`
+    /// 2) Map endpoints using `maps_to`, falling back to the nearest mapped ancestor
+    ///    (implicit mapping)
     ///    - if either endpoint unmapped => mark impl edge Unmapped and return
-    /// 3) Create/reuse propagated edge (from_arch -> to_arch, same kind)
+    /// 3) Create/reuse propagated edge (from_arch -> to_arch, propagation kind of the edge)
     /// 4) Increment propagated edge counter
     /// 5) Record: propagation_table[prop_edge] includes impl_edge
     pub fn propagate_impl_edge(&mut self, impl_edge_id: EdgeId) -> Result<(), GraphError> {
@@ -68,11 +77,11 @@ impl ReflexionGraph {
                 });
             }
 
-            (e.from, e.to, e.kind.clone())
+            (e.from, e.to, e.kind)
         };
 
         // Map endpoints: impl -> arch
-        let from_arch = match self.maps_to.get(&from_impl).copied() {
+        let from_arch = match self.effective_mapping(from_impl) {
             Some(x) => x,
             None => {
                 if let Some(e) = self.edges.get_mut(&impl_edge_id) {
@@ -82,7 +91,7 @@ impl ReflexionGraph {
             }
         };
 
-        let to_arch = match self.maps_to.get(&to_impl).copied() {
+        let to_arch = match self.effective_mapping(to_impl) {
             Some(x) => x,
             None => {
                 if let Some(e) = self.edges.get_mut(&impl_edge_id) {
@@ -93,7 +102,7 @@ impl ReflexionGraph {
         };
 
         // Create/reuse propagated edge in architecture space
-        let prop_id = self.get_or_create_propagated_edge(from_arch, to_arch, kind)?;
+        let prop_id = self.get_or_create_propagated_edge_id(from_arch, to_arch, self.propagation_kind(kind))?;
 
         // Increment propagated edge counter
         if let Some(pe) = self.edges.get_mut(&prop_id) {
@@ -103,8 +112,9 @@ impl ReflexionGraph {
         // Record the relationship: propagated edge <- impl edge(s)
         self.propagation_table
             .entry(prop_id)
-            .or_insert_with(HashSet::new)
+            .or_default()
             .insert(impl_edge_id);
+        self.impl_to_prop.insert(impl_edge_id, prop_id);
 
         Ok(())
     }
@@ -140,9 +150,9 @@ mod tests {
     use super::*;
     use crate::core::graph::{Edge, Node, ReflexionGraph};
     use crate::core::state::EdgeState;
-    use crate::core::types::{EdgeKind, SubgraphKind};
+    use crate::core::types::{EdgeKind, KindId, SubgraphKind};
 
-    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: EdgeKind) -> Edge {
+    fn mk_edge(from: u32, to: u32, subgraph: SubgraphKind, kind: KindId) -> Edge {
         Edge {
             id: 0, // overwritten by add_edge
             from,
@@ -151,6 +161,7 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            attrs: Default::default(),
         }
     }
 
@@ -182,7 +193,7 @@ mod tests {
                 login_page,
                 user_service,
                 SubgraphKind::Implementation,
-                EdgeKind::calls(),
+                KindId::CALLS,
             ))
             .unwrap();
 
@@ -195,9 +206,9 @@ mod tests {
             if e.subgraph == SubgraphKind::Propagated
             && e.from == ui
             && e.to == service
-            && e.kind == EdgeKind::calls()
+            && e.kind == KindId::CALLS
             {
-                found_prop = Some(*eid);
+                found_prop = Some(eid);
                 assert_eq!(e.counter, 1, "propagated edge counter must be incremented");
             }
         }
@@ -242,10 +253,10 @@ mod tests {
         g.set_mapping(c, service).unwrap();
 
         let e1 = g
-            .add_edge(mk_edge(a, b, SubgraphKind::Implementation, EdgeKind::calls()))
+            .add_edge(mk_edge(a, b, SubgraphKind::Implementation, KindId::CALLS))
             .unwrap();
         let e2 = g
-            .add_edge(mk_edge(a, c, SubgraphKind::Implementation, EdgeKind::calls()))
+            .add_edge(mk_edge(a, c, SubgraphKind::Implementation, KindId::CALLS))
             .unwrap();
 
         g.propagate_impl_edge(e1).unwrap();
@@ -259,9 +270,9 @@ mod tests {
                 if e.subgraph == SubgraphKind::Propagated
                 && e.from == ui
                 && e.to == service
-                && e.kind == EdgeKind::calls()
+                && e.kind == KindId::CALLS
                 {
-                    Some((*eid, e.counter))
+                    Some((eid, e.counter))
                 } else {
                     None
                 }
@@ -285,7 +296,7 @@ mod tests {
 
         // Architecture nodes
         let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
-        let service = g
+        let _service = g
             .add_node(Node::new("Service", SubgraphKind::Architecture, None))
             .unwrap();
 
@@ -306,7 +317,7 @@ mod tests {
                 login_page,
                 user_service,
                 SubgraphKind::Implementation,
-                EdgeKind::calls(),
+                KindId::CALLS,
             ))
             .unwrap();
 
@@ -333,7 +344,7 @@ mod tests {
         let a2 = g.add_node(Node::new("A2", SubgraphKind::Architecture, None)).unwrap();
 
         let arch_edge_id = g
-            .add_edge(mk_edge(a1, a2, SubgraphKind::Architecture, EdgeKind::depends_on()))
+            .add_edge(mk_edge(a1, a2, SubgraphKind::Architecture, KindId::DEPENDS_ON))
             .unwrap();
 
         let err = g.propagate_impl_edge(arch_edge_id).unwrap_err();
@@ -363,7 +374,7 @@ mod tests {
                 ui,
                 service,
                 SubgraphKind::Architecture,
-                EdgeKind::calls(),
+                KindId::CALLS,
             ))
             .unwrap();
 
@@ -0,0 +1,225 @@
+// mapping recommendations for unmapped implementation nodes
+//
+// Attraction-function clustering in the style of CountAttract: a node is attracted to an
+// architecture component C by its dependencies on implementation nodes already mapped into C
+// (weight 1), and to a lesser degree (weight phi) by dependencies on nodes mapped elsewhere
+// that the architecture would accept if the node were mapped to C (the edge would lift).
+// Dependencies that would become violations under C contribute nothing.
+//
+// confidence(n, C) = attraction(n, C) / number of n's dependencies with a mapped other end,
+// so 1.0 means "every known dependency agrees with C".
+use std::collections::{HashMap, HashSet};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecommendConfig {
+    pub phi: f64, //weight of dependencies into other components that the architecture allows
+    pub min_confidence: f64, //candidates below this are dropped
+    pub max_candidates: usize, //per node
+}
+
+impl Default for RecommendConfig {
+    fn default() -> Self {
+        Self { phi: 0.5, min_confidence: 0.0, max_candidates: 5 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub arch_node: NodeId,
+    pub attraction: f64,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub impl_node: NodeId,
+    pub candidates: Vec<Candidate>, //best first
+}
+
+impl Recommendation {
+    pub fn best(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+}
+
+impl ReflexionGraph {
+    /// Rank candidate components for every implementation node without an (implicit) mapping.
+    /// Nodes without any candidate are left out; results are ordered by node id.
+    pub fn recommend_mappings(&self, config: &RecommendConfig) -> Result<Vec<Recommendation>, GraphError> {
+        let unmapped: Vec<NodeId> = self
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Implementation && self.effective_mapping(n.id).is_none())
+            .map(|n| n.id)
+            .collect();
+
+        //incident implementation edges of the unmapped nodes, gathered in one pass (in id order)
+        let wanted: HashSet<NodeId> = unmapped.iter().copied().collect();
+        let mut incident: HashMap<NodeId, Vec<EdgeId>> = HashMap::new();
+        for e in self.edges.values().filter(|e| e.subgraph == SubgraphKind::Implementation) {
+            for end in [e.from, e.to] {
+                if wanted.contains(&end) {
+                    incident.entry(end).or_default().push(e.id);
+                }
+                if e.from == e.to {
+                    break;
+                }
+            }
+        }
+
+        let mut recs = Vec::new();
+        for n in unmapped {
+            let edges = incident.get(&n).map(Vec::as_slice).unwrap_or_default();
+            let rec = self.rank_candidates(n, edges, config)?;
+            if !rec.candidates.is_empty() {
+                recs.push(rec);
+            }
+        }
+        Ok(recs)
+    }
+
+    /// Rank candidate components for one implementation node (mapped or not).
+    pub fn recommend_mapping_for(&self, impl_node: NodeId, config: &RecommendConfig) -> Result<Recommendation, GraphError> {
+        let found = self.node_subgraph(impl_node)?;
+        if found != SubgraphKind::Implementation {
+            return Err(GraphError::WrongSubgraph {
+                node: impl_node,
+                expected: SubgraphKind::Implementation,
+                found,
+            });
+        }
+        self.rank_candidates(impl_node, &self.incident_impl_edges(impl_node), config)
+    }
+
+    //`incident`: the implementation edges touching impl_node
+    fn rank_candidates(&self, impl_node: NodeId, incident: &[EdgeId], config: &RecommendConfig) -> Result<Recommendation, GraphError> {
+        //(other end's component, kind, outgoing?) for each dependency with a mapped other end
+        let deps: Vec<_> = incident
+            .iter()
+            .filter_map(|eid| {
+                let e = &self.edges[eid];
+                let (other, outgoing) = if e.from == impl_node { (e.to, true) } else { (e.from, false) };
+                if other == impl_node {
+                    return None;
+                }
+                Some((self.effective_mapping(other)?, e.kind, outgoing))
+            })
+            .collect();
+
+        let mut candidates = Vec::new();
+        if !deps.is_empty() {
+            let mut arch_nodes: Vec<NodeId> = self
+                .nodes
+                .values()
+                .filter(|n| n.subgraph == SubgraphKind::Architecture)
+                .map(|n| n.id)
+                .collect();
+            arch_nodes.sort_unstable();
+
+            let mut attraction: HashMap<NodeId, f64> = HashMap::new();
+            for &c in &arch_nodes {
+                let mut score = 0.0;
+                for &(other_arch, kind, outgoing) in &deps {
+                    if other_arch == c {
+                        score += 1.0;
+                        continue;
+                    }
+                    let (from, to) = if outgoing { (c, other_arch) } else { (other_arch, c) };
+                    if self.forbidding_rule_id(from, to, kind).is_none() && self.lift_id(from, to, kind)?.is_some() {
+                        score += config.phi;
+                    }
+                }
+                if score > 0.0 {
+                    attraction.insert(c, score);
+                }
+            }
+
+            let overall = deps.len() as f64;
+            candidates = arch_nodes
+                .into_iter()
+                .filter_map(|c| {
+                    let a = *attraction.get(&c)?;
+                    Some(Candidate { arch_node: c, attraction: a, confidence: a / overall })
+                })
+                .filter(|c| c.confidence >= config.min_confidence)
+                .collect();
+            //best first, ties by id (arch_nodes was sorted, sort_by is stable)
+            candidates.sort_by(|a, b| b.attraction.total_cmp(&a.attraction));
+            candidates.truncate(config.max_candidates);
+        }
+
+        Ok(Recommendation { impl_node, candidates })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecommendConfig;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn ranks_components_by_attraction() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+        g.add_arch_edge(service, db, EdgeKind::calls()).unwrap();
+
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, None)).unwrap();
+        let usersvc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let repo = g.add_node(Node::new("UserRepo", SubgraphKind::Implementation, None)).unwrap();
+        let helper = g.add_node(Node::new("AuthHelper", SubgraphKind::Implementation, None)).unwrap();
+        let lonely = g.add_node(Node::new("Lonely", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(page, ui).unwrap();
+        g.set_mapping(usersvc, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+
+        //AuthHelper is called by UserService and calls UserRepo
+        g.add_impl_edge(usersvc, helper, EdgeKind::calls()).unwrap();
+        g.add_impl_edge(helper, repo, EdgeKind::calls()).unwrap();
+
+        let recs = g.recommend_mappings(&RecommendConfig::default()).unwrap();
+        assert_eq!(recs.len(), 1); //Lonely has no mapped dependency, so no candidate
+        let rec = &recs[0];
+        assert_eq!(rec.impl_node, helper);
+
+        //Service: one dependency inside (1.0) + Service -> DB is specified (phi)
+        let best = rec.best().unwrap();
+        assert_eq!(best.arch_node, service);
+        assert_eq!(best.attraction, 1.5);
+        assert_eq!(best.confidence, 0.75);
+
+        //DB: one dependency inside, Service -> DB is also specified
+        assert_eq!(rec.candidates[1].arch_node, db);
+        assert_eq!(rec.candidates[1].attraction, 1.5);
+        //UI: both dependencies would be divergent
+        assert!(rec.candidates.iter().all(|c| c.arch_node != ui));
+
+        let strict = RecommendConfig { min_confidence: 0.8, ..RecommendConfig::default() };
+        assert!(g.recommend_mapping_for(helper, &strict).unwrap().candidates.is_empty());
+        assert!(g.recommend_mapping_for(lonely, &strict).unwrap().candidates.is_empty());
+        assert!(matches!(g.recommend_mapping_for(ui, &strict), Err(GraphError::WrongSubgraph { .. })));
+    }
+
+    #[test]
+    fn inherited_mappings_count_and_inheriting_nodes_are_not_recommended() {
+        let mut g = ReflexionGraph::new();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+
+        let pkg = g.add_node(Node::new("service", SubgraphKind::Implementation, None)).unwrap();
+        let inner = g.add_node(Node::new("Inner", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let outer = g.add_node(Node::new("Outer", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(pkg, service).unwrap();
+        g.add_impl_edge(outer, inner, EdgeKind::calls()).unwrap();
+
+        let recs = g.recommend_mappings(&RecommendConfig::default()).unwrap();
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].impl_node, outer);
+        assert_eq!(recs[0].best().unwrap().arch_node, service);
+        assert_eq!(recs[0].best().unwrap().confidence, 1.0);
+    }
+}
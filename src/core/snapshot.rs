@@ -0,0 +1,537 @@
+// versioned on-disk snapshots of an analysed graph
+//
+// A snapshot holds everything the incremental API needs to continue where the previous run
+// stopped: nodes, edges with states and counters, mappings, propagation/lifting bookkeeping,
+// rules and the id counters. Derived indexes (adjacency, (from, to, kind) indexes, the
+// impl-to-propagated and arch-to-propagated reverse indexes) are not stored; they are rebuilt
+// on load.
+//
+// The file format is JSON. Maps are ordered, so saving the same graph twice gives the same
+// bytes. `format_version` is bumped whenever the layout changes; older or newer versions are
+// rejected instead of being half-read.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::core::forbidden::ForbiddenRule;
+use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+use crate::core::interner::KindInterner;
+use crate::core::layers::Layering;
+use crate::core::mapping::MappingRule;
+use crate::core::types::{EdgeId, EdgeKind, KindId, NodeId, NodeKind, RuleId, SubgraphKind};
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    format_version: u32,
+    next_node_id: NodeId,
+    next_edge_id: EdgeId,
+    next_rule_id: RuleId,
+    kinds: Vec<EdgeKind>, //interned kinds, indexed by KindId
+    kind_parents: Vec<(KindId, KindId)>,
+    nodes: Vec<Node>, //ordered by id
+    edges: Vec<Edge>, //ordered by id
+    maps_to: BTreeMap<NodeId, NodeId>,
+    propagation_table: BTreeMap<EdgeId, BTreeSet<EdgeId>>,
+    lifted_to: BTreeMap<EdgeId, EdgeId>,
+    allowed_edges: BTreeSet<EdgeId>,
+    forbidden_rules: BTreeMap<RuleId, ForbiddenRule>,
+    forbidden_by: BTreeMap<EdgeId, RuleId>,
+    layering: Option<Layering>,
+    layer_edges: Vec<EdgeId>,
+    #[serde(default)]
+    layer_subtype: bool, //calls <: depends_on in kind_parents belongs to the layering
+    mapping_rules: BTreeMap<RuleId, MappingRule>,
+    next_mapping_rule_id: RuleId,
+    rule_mapped: BTreeMap<NodeId, RuleId>,
+    mapping_conflicts: Vec<SavedConflict>,
+    kind_restrictions: BTreeMap<NodeId, Vec<NodeKind>>,
+}
+
+impl GraphSnapshot {
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+}
+
+//a mapping conflict (GraphError::MappingAlreadyExists) as stored; errors themselves are not
+//part of the file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct SavedConflict {
+    impl_node: NodeId,
+    old_arch: NodeId,
+    new_arch: NodeId,
+}
+
+impl From<SavedConflict> for GraphError {
+    fn from(c: SavedConflict) -> Self {
+        GraphError::MappingAlreadyExists { impl_node: c.impl_node, old_arch: c.old_arch, new_arch: c.new_arch }
+    }
+}
+
+//read first, so a snapshot from another version is rejected by number, not by a parse error
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    format_version: u32,
+}
+
+impl ReflexionGraph {
+    pub fn snapshot(&self) -> GraphSnapshot {
+        let mut kind_parents: Vec<(KindId, KindId)> = self.kind_parents.iter().map(|(&k, &p)| (k, p)).collect();
+        kind_parents.sort_unstable();
+
+        GraphSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            next_node_id: self.next_node_id,
+            next_edge_id: self.next_edge_id,
+            next_rule_id: self.next_rule_id,
+            kinds: self.kinds.iter().cloned().collect(),
+            kind_parents,
+            nodes: self.nodes.values().cloned().collect(),
+            edges: self.edges.values().cloned().collect(),
+            maps_to: self.maps_to.iter().map(|(&k, &v)| (k, v)).collect(),
+            propagation_table: self
+                .propagation_table
+                .iter()
+                .map(|(&prop, impls)| (prop, impls.iter().copied().collect()))
+                .collect(),
+            lifted_to: self.lifted_to.iter().map(|(&k, &v)| (k, v)).collect(),
+            allowed_edges: self.allowed_edges.iter().copied().collect(),
+            forbidden_rules: self.forbidden_rules.clone(),
+            forbidden_by: self.forbidden_by.iter().map(|(&k, &v)| (k, v)).collect(),
+            layering: self.layering.clone(),
+            layer_edges: self.layer_edges.clone(),
+            layer_subtype: self.layer_subtype,
+            mapping_rules: self.mapping_rules.clone(),
+            next_mapping_rule_id: self.next_mapping_rule_id,
+            rule_mapped: self.rule_mapped.iter().map(|(&k, &v)| (k, v)).collect(),
+            mapping_conflicts: self
+                .mapping_conflicts
+                .iter()
+                .filter_map(|c| match *c {
+                    GraphError::MappingAlreadyExists { impl_node, old_arch, new_arch } => {
+                        Some(SavedConflict { impl_node, old_arch, new_arch })
+                    }
+                    _ => None,
+                })
+                .collect(),
+            kind_restrictions: self
+                .kind_restrictions
+                .iter()
+                .map(|(&arch, kinds)| {
+                    let mut kinds: Vec<NodeKind> = kinds.iter().cloned().collect();
+                    kinds.sort_by_cached_key(|k| (k.to_string(), matches!(k, NodeKind::Custom(_))));
+                    (arch, kinds)
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild a graph from a snapshot, including the derived indexes. The result can be
+    /// updated incrementally right away, no run_from_scratch needed. Every id the snapshot
+    /// refers to is checked first; an inconsistent snapshot gives `GraphError::Snapshot`.
+    pub fn from_snapshot(snapshot: GraphSnapshot) -> Result<Self, GraphError> {
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(GraphError::UnsupportedSnapshotVersion {
+                found: snapshot.format_version,
+                supported: SNAPSHOT_FORMAT_VERSION,
+            });
+        }
+        let corrupt = |what: String| GraphError::Snapshot(format!("inconsistent snapshot: {}", what));
+
+        let mut g = ReflexionGraph::new();
+        g.next_node_id = snapshot.next_node_id;
+        g.next_edge_id = snapshot.next_edge_id;
+        g.next_rule_id = snapshot.next_rule_id;
+
+        //kinds must come back with the same ids, the predefined ones included
+        g.kinds = KindInterner::new();
+        for (i, kind) in snapshot.kinds.iter().enumerate() {
+            if g.kinds.intern(kind).index() as usize != i {
+                return Err(corrupt(format!("edge kind {} out of order", kind)));
+            }
+        }
+        for (kind, parent) in snapshot.kind_parents {
+            for k in [kind, parent] {
+                if k.index() as usize >= g.kinds.len() {
+                    return Err(corrupt(format!("unknown edge kind id {}", k.index())));
+                }
+            }
+            if g.kind_parents.insert(kind, parent).is_some() {
+                return Err(corrupt(format!("edge kind id {} has two supertypes", kind.index())));
+            }
+        }
+        //a chain longer than the number of kinds must revisit one
+        for &kind in g.kind_parents.keys() {
+            let mut current = kind;
+            for _ in 0..=g.kinds.len() {
+                match g.kind_parents.get(&current) {
+                    Some(&parent) => current = parent,
+                    None => break,
+                }
+            }
+            if g.kind_parents.contains_key(&current) {
+                return Err(corrupt(format!("edge kind id {} is its own supertype", kind.index())));
+            }
+        }
+
+        for node in snapshot.nodes {
+            if node.id >= g.next_node_id {
+                return Err(corrupt(format!("node id {} not below next id {}", node.id, g.next_node_id)));
+            }
+            let id = node.id;
+            if g.nodes.insert(id, node).is_some() {
+                return Err(corrupt(format!("node id {} appears twice", id)));
+            }
+        }
+        for node in g.nodes.values() {
+            if let Some(parent) = node.parent {
+                let ok = g
+                    .nodes
+                    .get(&parent)
+                    .is_some_and(|p| p.subgraph == node.subgraph && p.children.contains(&node.id));
+                if !ok {
+                    return Err(corrupt(format!("node {} has an invalid parent {}", node.id, parent)));
+                }
+            }
+            if let Some(&child) = node.children.iter().find(|c| g.nodes.get(c).and_then(|c| c.parent) != Some(node.id)) {
+                return Err(corrupt(format!("node {} lists {} as a child", node.id, child)));
+            }
+        }
+        for edge in snapshot.edges {
+            if edge.id >= g.next_edge_id {
+                return Err(corrupt(format!("edge id {} not below next id {}", edge.id, g.next_edge_id)));
+            }
+            for end in [edge.from, edge.to] {
+                if !g.nodes.contains_key(&end) {
+                    return Err(corrupt(format!("edge {} ends at unknown node {}", edge.id, end)));
+                }
+            }
+            if edge.kind.index() as usize >= g.kinds.len() {
+                return Err(corrupt(format!("edge {} has unknown kind id {}", edge.id, edge.kind.index())));
+            }
+            let id = edge.id;
+            if g.edges.insert(id, edge).is_some() {
+                return Err(corrupt(format!("edge id {} appears twice", id)));
+            }
+            g.index_edge(id);
+        }
+
+        g.maps_to = snapshot.maps_to.into_iter().collect();
+        for (prop, impls) in snapshot.propagation_table {
+            for &ie in &impls {
+                g.impl_to_prop.insert(ie, prop);
+            }
+            g.propagation_table.insert(prop, impls.into_iter().collect());
+        }
+        for (prop_id, arch_eid) in snapshot.lifted_to {
+            g.record_lift(prop_id, arch_eid);
+        }
+        g.allowed_edges = snapshot.allowed_edges.into_iter().collect();
+        g.forbidden_rules = snapshot.forbidden_rules;
+        let rule_kinds: Vec<KindId> = g.forbidden_rules.values().filter_map(|r| g.kinds.get(r.kind.as_ref()?)).collect();
+        for kind in rule_kinds {
+            g.use_kind(kind);
+        }
+        g.forbidden_by = snapshot.forbidden_by.into_iter().collect();
+        g.layering = snapshot.layering;
+        g.layer_edges = snapshot.layer_edges;
+        g.layer_subtype = snapshot.layer_subtype;
+        g.mapping_rules = snapshot.mapping_rules;
+        g.next_mapping_rule_id = snapshot.next_mapping_rule_id;
+        g.rule_mapped = snapshot.rule_mapped.into_iter().collect();
+        g.mapping_conflicts = snapshot.mapping_conflicts.into_iter().map(GraphError::from).collect();
+        g.kind_restrictions = snapshot
+            .kind_restrictions
+            .into_iter()
+            .map(|(arch, kinds)| (arch, kinds.into_iter().collect::<HashSet<_>>()))
+            .collect::<HashMap<_, _>>();
+
+        g.check_snapshot_references().map_err(corrupt)?;
+        Ok(g)
+    }
+
+    //the ids stored outside nodes/edges (checked while loading those) must exist and be of
+    //the right subgraph; the message names the first offender
+    fn check_snapshot_references(&self) -> Result<(), String> {
+        let node_in = |id: NodeId, sg: SubgraphKind| self.nodes.get(&id).is_some_and(|n| n.subgraph == sg);
+        let edge_in = |id: EdgeId, sg: SubgraphKind| self.edges.get(&id).is_some_and(|e| e.subgraph == sg);
+        use SubgraphKind::{Architecture, Implementation, Propagated};
+
+        for (&impl_node, &arch_node) in &self.maps_to {
+            if !node_in(impl_node, Implementation) || !node_in(arch_node, Architecture) {
+                return Err(format!("mapping {} -> {}", impl_node, arch_node));
+            }
+        }
+        for (&prop, impls) in &self.propagation_table {
+            if !edge_in(prop, Propagated) {
+                return Err(format!("propagation table entry for edge {}", prop));
+            }
+            if let Some(&ie) = impls.iter().find(|&&ie| !edge_in(ie, Implementation) || self.impl_to_prop.get(&ie) != Some(&prop)) {
+                return Err(format!("implementation edge {} behind propagated edge {}", ie, prop));
+            }
+        }
+        for (&prop, &arch) in &self.lifted_to {
+            if !edge_in(prop, Propagated) || !edge_in(arch, Architecture) {
+                return Err(format!("lift of edge {} to edge {}", prop, arch));
+            }
+        }
+        for (&prop, rule) in &self.forbidden_by {
+            if !edge_in(prop, Propagated) || !self.forbidden_rules.contains_key(rule) {
+                return Err(format!("edge {} forbidden by rule {}", prop, rule));
+            }
+        }
+        if let Some(&id) = self.allowed_edges.iter().chain(&self.layer_edges).find(|&&id| !edge_in(id, Architecture)) {
+            return Err(format!("allowed edge {}", id));
+        }
+        for (&id, rule) in &self.forbidden_rules {
+            if id >= self.next_rule_id || [rule.from, rule.to].into_iter().flatten().any(|n| !node_in(n, Architecture)) {
+                return Err(format!("forbidden rule {}", id));
+            }
+        }
+        for (&id, rule) in &self.mapping_rules {
+            if id >= self.next_mapping_rule_id || !node_in(rule.target, Architecture) {
+                return Err(format!("mapping rule {}", id));
+            }
+        }
+        for (&impl_node, rule) in &self.rule_mapped {
+            if !self.maps_to.contains_key(&impl_node) || !self.mapping_rules.contains_key(rule) {
+                return Err(format!("rule mapping of node {} by rule {}", impl_node, rule));
+            }
+        }
+        if let Some(&arch) = self.kind_restrictions.keys().find(|&&a| !node_in(a, Architecture)) {
+            return Err(format!("kind restriction on node {}", arch));
+        }
+        if let Some(&node) = self.layering.iter().flat_map(|l| l.layers.iter().flatten()).find(|&&n| !node_in(n, Architecture)) {
+            return Err(format!("layer node {}", node));
+        }
+        if self.layer_subtype && (self.layering.is_none() || self.kind_parents.get(&KindId::CALLS) != Some(&KindId::DEPENDS_ON)) {
+            return Err("kind registration of the layering".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn write_snapshot<W: Write>(&self, writer: W) -> Result<(), GraphError> {
+        serde_json::to_writer(writer, &self.snapshot()).map_err(|e| match e.classify() {
+            serde_json::error::Category::Io => GraphError::io(Path::new("<writer>"), e.into()),
+            _ => GraphError::Snapshot(e.to_string()),
+        })
+    }
+
+    pub fn read_snapshot<R: Read>(mut reader: R) -> Result<Self, GraphError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(|e| GraphError::io(Path::new("<reader>"), e))?;
+
+        let header: SnapshotHeader = serde_json::from_str(&text).map_err(|e| GraphError::Snapshot(e.to_string()))?;
+        if header.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(GraphError::UnsupportedSnapshotVersion {
+                found: header.format_version,
+                supported: SNAPSHOT_FORMAT_VERSION,
+            });
+        }
+        let snapshot: GraphSnapshot = serde_json::from_str(&text).map_err(|e| GraphError::Snapshot(e.to_string()))?;
+        Self::from_snapshot(snapshot)
+    }
+
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<(), GraphError> {
+        let path = path.as_ref();
+        let file = fs::File::create(path).map_err(|e| GraphError::io(path, e))?;
+        let mut out = std::io::BufWriter::new(file);
+        self.write_snapshot(&mut out).map_err(|e| match e {
+            GraphError::Io { message, .. } => GraphError::Io { path: path.display().to_string(), message },
+            other => other,
+        })?;
+        out.flush().map_err(|e| GraphError::io(path, e))
+    }
+
+    pub fn load_snapshot(path: impl AsRef<Path>) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| GraphError::io(path, e))?;
+        Self::read_snapshot(text.as_bytes())
+    }
+}
+
+//the graph itself (de)serializes as its snapshot
+impl Serialize for ReflexionGraph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ReflexionGraph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = GraphSnapshot::deserialize(deserializer)?;
+        ReflexionGraph::from_snapshot(snapshot).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GraphSnapshot, SavedConflict, SNAPSHOT_FORMAT_VERSION};
+    use crate::core::forbidden::ForbiddenRule;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::layers::Layering;
+    use crate::core::mapping::MappingRule;
+    use crate::core::types::{AttrValue, EdgeKind, KindId, NodeKind, SubgraphKind};
+
+    fn analysed() -> ReflexionGraph {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+        g.set_layering(Layering::relaxed(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        g.add_forbidden_rule(ForbiddenRule::new(ui, db)).unwrap();
+        g.restrict_mapping_kinds(db, [NodeKind::ClassNode, NodeKind::custom("Repository")]).unwrap();
+        g.add_mapping_rule(MappingRule::glob("src/ui/**", ui)).unwrap();
+
+        let src = g.add_node(Node::new("src", SubgraphKind::Implementation, None)).unwrap();
+        let ui_pkg = g.add_node(Node::new("ui", SubgraphKind::Implementation, Some(src))).unwrap();
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, Some(ui_pkg))).unwrap();
+        let svc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None).with_attr("owner", "auth")).unwrap();
+        let repo = g
+            .add_node(Node::new("UserRepo", SubgraphKind::Implementation, None).with_kind(NodeKind::ClassNode))
+            .unwrap();
+        g.set_mapping(svc, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+
+        g.add_impl_edge(page, svc, EdgeKind::calls()).unwrap();
+        g.add_impl_edge(svc, repo, EdgeKind::from("imports")).unwrap();
+        let bad = g.add_impl_edge(page, repo, EdgeKind::calls()).unwrap();
+        g.set_edge_attr(bad, "line", 42).unwrap();
+        g.run_from_scratch().unwrap();
+        g
+    }
+
+    fn assert_same(a: &ReflexionGraph, b: &ReflexionGraph) {
+        assert!(a.nodes().eq(b.nodes()));
+        assert!(a.edges().eq(b.edges()));
+        assert_eq!(a.maps_to, b.maps_to);
+        assert_eq!(a.propagation_table, b.propagation_table);
+        assert_eq!(a.impl_to_prop, b.impl_to_prop);
+        assert_eq!(a.lifted_to, b.lifted_to);
+        assert_eq!(a.lifted_from, b.lifted_from);
+        assert_eq!(a.forbidden_by, b.forbidden_by);
+        assert_eq!(a.arch_index, b.arch_index);
+        assert_eq!(a.prop_index, b.prop_index);
+        assert_eq!(a.kind_refs, b.kind_refs);
+        assert_eq!(a.impl_out.iter().collect::<Vec<_>>(), b.impl_out.iter().collect::<Vec<_>>());
+        assert_eq!(a.impl_in.iter().collect::<Vec<_>>(), b.impl_in.iter().collect::<Vec<_>>());
+        assert_eq!(a.arch_out.iter().collect::<Vec<_>>(), b.arch_out.iter().collect::<Vec<_>>());
+        assert_eq!(a.arch_in.iter().collect::<Vec<_>>(), b.arch_in.iter().collect::<Vec<_>>());
+        assert_eq!((a.next_node_id, a.next_edge_id, a.next_rule_id), (b.next_node_id, b.next_edge_id, b.next_rule_id));
+        assert_eq!(a.next_mapping_rule_id, b.next_mapping_rule_id);
+        assert_eq!(a.layer_subtype, b.layer_subtype);
+    }
+
+    #[test]
+    fn round_trip_keeps_the_analysis_and_incremental_updates_continue() {
+        let mut g = analysed();
+        let mut bytes = Vec::new();
+        g.write_snapshot(&mut bytes).unwrap();
+        let mut loaded = ReflexionGraph::read_snapshot(bytes.as_slice()).unwrap();
+        assert_same(&g, &loaded);
+
+        //same graph, same bytes
+        let mut again = Vec::new();
+        loaded.write_snapshot(&mut again).unwrap();
+        assert_eq!(bytes, again);
+
+        let bad = g.edges_with_state(crate::core::state::EdgeState::Forbidden).next().unwrap().id();
+        assert_eq!(loaded.edge_state(bad), g.edge_state(bad));
+        assert_eq!(loaded.edge(bad).unwrap().attrs().get("line"), Some(&AttrValue::Int(42)));
+        assert_eq!(loaded.mapping_rules().count(), 1);
+
+        //the same edit on both sides gives the same graph
+        let page = g.nodes().find(|n| n.name() == "LoginPage").unwrap().id();
+        let svc = g.nodes().find(|n| n.name() == "UserService").unwrap().id();
+        for graph in [&mut g, &mut loaded] {
+            graph.add_impl_edge(svc, page, EdgeKind::calls()).unwrap();
+            let extra = graph.add_node(Node::new("Widget", SubgraphKind::Implementation, Some(page))).unwrap();
+            graph.add_impl_edge(extra, svc, EdgeKind::calls()).unwrap();
+        }
+        assert_same(&g, &loaded);
+    }
+
+    #[test]
+    fn inconsistent_snapshots_are_rejected() {
+        let g = analysed();
+        let corrupt = |edit: &dyn Fn(&mut GraphSnapshot)| {
+            let mut snapshot = g.snapshot();
+            edit(&mut snapshot);
+            match ReflexionGraph::from_snapshot(snapshot) {
+                Err(GraphError::Snapshot(message)) => message,
+                other => panic!("expected a snapshot error, got {:?}", other.map(|_| ())),
+            }
+        };
+        let calls = KindId::CALLS;
+        let depends_on = KindId::DEPENDS_ON;
+        let unknown = g.kinds.len() as u32;
+        let prop = *g.lifted_to.keys().min().unwrap();
+        let rule = *g.forbidden_by.values().next().unwrap();
+
+        corrupt(&|s| s.kind_parents.push((calls, KindId(unknown))));
+        corrupt(&|s| s.kind_parents = vec![(calls, depends_on), (depends_on, calls)]);
+        corrupt(&|s| s.layering = None);
+        corrupt(&|s| {
+            s.maps_to.insert(9999, 1);
+        });
+        corrupt(&|s| s.maps_to = s.maps_to.keys().map(|&i| (i, i)).collect());
+        corrupt(&|s| {
+            s.propagation_table.entry(9999).or_default().insert(1);
+        });
+        corrupt(&|s| {
+            s.lifted_to.insert(prop, 9999);
+        });
+        corrupt(&|s| {
+            s.forbidden_by.insert(prop, rule + 100);
+        });
+        corrupt(&|s| {
+            s.allowed_edges.insert(prop);
+        });
+        corrupt(&|s| s.nodes.last_mut().unwrap().parent = Some(9999));
+        let message = corrupt(&|s| s.edges[0].to = 9999);
+        assert!(message.contains("unknown node 9999"), "{}", message);
+
+        //mapping conflicts survive as plain rows
+        let mut snapshot = g.snapshot();
+        snapshot.mapping_conflicts.push(SavedConflict { impl_node: 1, old_arch: 2, new_arch: 3 });
+        let loaded = ReflexionGraph::from_snapshot(snapshot).unwrap();
+        assert_eq!(
+            loaded.mapping_conflicts().last(),
+            Some(&GraphError::MappingAlreadyExists { impl_node: 1, old_arch: 2, new_arch: 3 })
+        );
+    }
+
+    #[test]
+    fn save_load_via_file_and_reject_other_versions() {
+        let g = analysed();
+        let path = std::env::temp_dir().join(format!("reflexion-snapshot-{}.json", std::process::id()));
+        g.save_snapshot(&path).unwrap();
+        let loaded = ReflexionGraph::load_snapshot(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_same(&g, &loaded);
+
+        //the graph is Serialize/Deserialize itself
+        let json = serde_json::to_string(&g).unwrap();
+        let via_serde: ReflexionGraph = serde_json::from_str(&json).unwrap();
+        assert_same(&g, &via_serde);
+
+        let newer = json.replacen(
+            &format!("\"format_version\":{}", SNAPSHOT_FORMAT_VERSION),
+            "\"format_version\":99",
+            1,
+        );
+        assert!(matches!(
+            ReflexionGraph::read_snapshot(newer.as_bytes()),
+            Err(GraphError::UnsupportedSnapshotVersion { found: 99, .. })
+        ));
+        assert!(matches!(ReflexionGraph::read_snapshot("{".as_bytes()), Err(GraphError::Snapshot(_))));
+        assert!(matches!(
+            ReflexionGraph::load_snapshot(std::env::temp_dir().join("no-such-reflexion-snapshot.json")),
+            Err(GraphError::Io { path, .. }) if path.ends_with("no-such-reflexion-snapshot.json")
+        ));
+    }
+}
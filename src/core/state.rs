@@ -1,5 +1,7 @@
 // convergent, divergent, etc..
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EdgeState {
     Undefined, //we don't know yet/missing data
     Specified, //edge exists in Architecture spec 
@@ -8,17 +10,34 @@ pub enum EdgeState {
     AllowedAbsent, //spec says it's optional, if it is missing it is okay
     Allowed, //spec doesn't mention it explicitly, but the spec allows it (allowed edge list) 
     Divergent, //code contains and edge that isn't specified and not allowed by rules/spec 
+    Forbidden, //code contains an edge that an explicit forbidden rule rules out (policy breach)
     Unmapped, //we can't compare because mapping is missing
 }
 
+//how bad a state is: drift from the spec (absent, divergent) vs. a hard policy breach (forbidden)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    None,
+    Drift,
+    PolicyBreach,
+}
+
 impl EdgeState {
-    //violation -> (absent, divergent)
+    //violation -> (absent, divergent, forbidden)
     //not a violation -> (convergent, allowed, allowedAbsent)
     //neither {analysis incomplete / undecided} -> (undefined, unmapped, specified)
     //violations -> architectural debt
     //undefined/unmapped -> tooling or modeling debt
     pub fn is_violation(&self) -> bool {
-        matches!(self, EdgeState::Absent | EdgeState::Divergent)
+        matches!(self, EdgeState::Absent | EdgeState::Divergent | EdgeState::Forbidden)
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            EdgeState::Forbidden => Severity::PolicyBreach,
+            EdgeState::Absent | EdgeState::Divergent => Severity::Drift,
+            _ => Severity::None,
+        }
     }
 
     pub fn is_unknown(&self) -> bool {
@@ -54,12 +73,21 @@ impl NodeState {
 
 #[cfg(test)]
 mod tests {
-    use super::EdgeState;
+    use super::{EdgeState, Severity};
 
     #[test]
-    fn is_violation_true_for_absent_and_divergent() {
+    fn is_violation_true_for_absent_divergent_and_forbidden() {
         assert!(EdgeState::Absent.is_violation());
         assert!(EdgeState::Divergent.is_violation());
+        assert!(EdgeState::Forbidden.is_violation());
+    }
+
+    #[test]
+    fn forbidden_is_more_severe_than_drift() {
+        assert_eq!(EdgeState::Forbidden.severity(), Severity::PolicyBreach);
+        assert_eq!(EdgeState::Divergent.severity(), Severity::Drift);
+        assert_eq!(EdgeState::Convergent.severity(), Severity::None);
+        assert!(EdgeState::Forbidden.severity() > EdgeState::Absent.severity());
     }
 
     #[test]
@@ -0,0 +1,107 @@
+// TOON reader/writer for model documents (see model.rs)
+//
+// TOON writes a list of uniform rows as one header plus one line per row, e.g.
+//   edges[2]{from,to,kind}:
+//     5,6,calls
+//     6,7,imports
+// which keeps large edge lists small for review tooling that feeds them to language models.
+use toon_format::{decode_default, encode_default, ToonError};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::model::{ModelDocument, ModelImport};
+
+impl ModelDocument {
+    pub fn to_toon(&self) -> Result<String, GraphError> {
+        encode_default(self).map_err(|e| GraphError::InvalidDocument(e.to_string()))
+    }
+
+    pub fn from_toon(text: &str) -> Result<Self, GraphError> {
+        decode_default(text).map_err(|e| match e {
+            ToonError::ParseError { line, column, message, .. } => GraphError::Parse { line, column, message },
+            other => GraphError::InvalidDocument(other.to_string()),
+        })
+    }
+}
+
+impl ReflexionGraph {
+    /// Architecture, implementation and mapping as a TOON document; with `with_results` the
+    /// edge states and counters are appended.
+    pub fn to_toon(&self, with_results: bool) -> Result<String, GraphError> {
+        self.to_model(with_results).to_toon()
+    }
+
+    /// Build and analyse a graph from a TOON document (see `from_model`).
+    pub fn from_toon(text: &str) -> Result<ModelImport, GraphError> {
+        ReflexionGraph::from_model(&ModelDocument::from_toon(text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::forbidden::ForbiddenRule;
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::layers::Layering;
+    use crate::core::model::ModelDocument;
+    use crate::core::state::EdgeState;
+    use crate::core::types::{AttrValue, EdgeKind, NodeKind, SubgraphKind};
+
+    fn classification(g: &ReflexionGraph) -> Vec<String> {
+        let mut out: Vec<String> = g
+            .edges()
+            .map(|e| format!("{:?} {}->{} {} {:?} {}", e.subgraph(), e.from(), e.to(), g.edge_kind(e.id()).unwrap(), e.state(), e.counter()))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn export_import_round_trip_with_results() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let service = g.add_node(Node::new("Service Layer", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("DB", SubgraphKind::Architecture, None).with_kind(NodeKind::DatastoreNode)).unwrap();
+        g.add_arch_edge(ui, service, EdgeKind::calls()).unwrap();
+        g.add_arch_edge(service, db, EdgeKind::calls()).unwrap();
+        g.add_allowed_edge(ui, db, EdgeKind::from("reads")).unwrap();
+
+        let src = g.add_node(Node::new("src", SubgraphKind::Implementation, None)).unwrap();
+        let page = g.add_node(Node::new("LoginPage", SubgraphKind::Implementation, Some(src))).unwrap();
+        let svc = g.add_node(Node::new("UserService", SubgraphKind::Implementation, None)).unwrap();
+        let repo = g.add_node(Node::new("UserRepo", SubgraphKind::Implementation, None).with_kind(NodeKind::custom("Repository"))).unwrap();
+        g.set_mapping(src, ui).unwrap();
+        g.set_mapping(svc, service).unwrap();
+        g.set_mapping(repo, db).unwrap();
+        g.add_impl_edge(page, svc, EdgeKind::calls()).unwrap();
+        g.add_impl_edge(page, repo, EdgeKind::calls()).unwrap();
+
+        //the rest of the inputs: kind hierarchy, forbidden rule, layering, attributes
+        g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+        g.add_forbidden_rule(ForbiddenRule::any_to(ui)).unwrap();
+        g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+        let imports = g.add_impl_edge(page, svc, EdgeKind::from("imports")).unwrap();
+        let upward = g.add_impl_edge(svc, page, EdgeKind::calls()).unwrap();
+        g.set_node_attr(svc, "owner", "accounts, core").unwrap();
+        g.set_edge_attr(upward, "line", 42).unwrap();
+        assert_eq!(g.edge_state(imports).unwrap(), EdgeState::Allowed);
+        assert_eq!(g.edge_state(upward).unwrap(), EdgeState::Forbidden);
+
+        let text = g.to_toon(true).unwrap();
+        assert!(text.contains("{from,to,kind}"), "edges are written as tables:\n{}", text);
+        let doc = ModelDocument::from_toon(&text).unwrap();
+        assert_eq!(doc, g.to_model(true));
+        let results = doc.results.as_ref().unwrap();
+        assert!(results.iter().any(|r| r.state == EdgeState::Divergent && r.subgraph == SubgraphKind::Propagated));
+        assert!(results.iter().any(|r| r.state == EdgeState::AllowedAbsent));
+
+        //node ids were assigned in the same order, so the re-imported graph is the same graph
+        //with the same analysis (edge ids follow the import order)
+        let imported = ReflexionGraph::from_toon(&g.to_toon(false).unwrap()).unwrap();
+        assert!(imported.node_ids.iter().all(|(doc_id, graph_id)| doc_id == graph_id));
+        assert!(imported.graph.nodes().eq(g.nodes()));
+        assert_eq!(classification(&imported.graph), classification(&g));
+        assert_eq!(imported.graph.to_model(false), g.to_model(false));
+        assert_eq!(imported.graph.node_attrs(svc).unwrap().get("owner"), Some(&AttrValue::from("accounts, core")));
+
+        assert!(matches!(ReflexionGraph::from_toon("architecture:\n  nodes[2]{id}:\n    1"), Err(GraphError::InvalidDocument(_))));
+        assert!(matches!(ModelDocument::from_toon("mapping: ["), Err(GraphError::Parse { line: 1, column: 11, .. })));
+    }
+}
@@ -1,18 +1,22 @@
+use std::collections::BTreeMap;
 use std::fmt;
+use serde::{Deserialize, Serialize};
 
 // enums + shared types
 pub type NodeId = u32;
 pub type EdgeId = u32;
 pub type Counter = i32;
+pub type RuleId = u32;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SubgraphKind {
     Architecture,
     Implementation,
     Propagated,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct EdgeKind(String);
 
 impl EdgeKind {
@@ -35,6 +39,21 @@ impl EdgeKind {
     }
 }
 
+//compact, interned edge kind (see interner.rs); resolve through ReflexionGraph::kind_name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct KindId(pub(crate) u32);
+
+impl KindId {
+    //predefined kinds, seeded by every interner in this order
+    pub const CONTAINS: KindId = KindId(0);
+    pub const CALLS: KindId = KindId(1);
+    pub const DEPENDS_ON: KindId = KindId(2);
+
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
 //Conversions
 impl From<&str> for EdgeKind {
     fn from(s: &str) -> Self {
@@ -62,7 +81,7 @@ impl AsRef<str> for EdgeKind {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeKind {
     ArchitectureNode,
     ImplementationNode,
@@ -85,3 +104,115 @@ impl NodeKind {
     }
 }
 
+//inverse of Display: predefined names give their variant, anything else is Custom
+impl From<&str> for NodeKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "ArchitectureNode" => NodeKind::ArchitectureNode,
+            "ImplementationNode" => NodeKind::ImplementationNode,
+            "DatastoreNode" => NodeKind::DatastoreNode,
+            "ServiceNode" => NodeKind::ServiceNode,
+            "UINode" => NodeKind::UINode,
+            "ModuleNode" => NodeKind::ModuleNode,
+            "ClassNode" => NodeKind::ClassNode,
+            "PackageNode" => NodeKind::PackageNode,
+            "FunctionNode" => NodeKind::FunctionNode,
+            other => NodeKind::custom(other),
+        }
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeKind::Custom(s) => write!(f, "{}", s),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+
+//typed attribute values for nodes and edges (source file, line, owner team, weight, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AttrValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl AttrValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttrValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AttrValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    //ints are widened, so weights can be given either way
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            AttrValue::Float(f) => Some(*f),
+            AttrValue::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttrValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(s: &str) -> Self {
+        AttrValue::Str(s.to_string())
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(s: String) -> Self {
+        AttrValue::Str(s)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(i: i64) -> Self {
+        AttrValue::Int(i)
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(f: f64) -> Self {
+        AttrValue::Float(f)
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(b: bool) -> Self {
+        AttrValue::Bool(b)
+    }
+}
+
+impl fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrValue::Str(s) => write!(f, "{}", s),
+            AttrValue::Int(i) => write!(f, "{}", i),
+            AttrValue::Float(x) => write!(f, "{}", x),
+            AttrValue::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+//ordered so reports and serialized output are deterministic
+pub type Attributes = BTreeMap<String, AttrValue>;
@@ -0,0 +1,37 @@
+// incremental updates agree with a full recomputation, seen through the public API
+use reflexion_core::core::builder::{EdgeBuilder, NodeBuilder};
+use reflexion_core::core::graph::ReflexionGraph;
+use reflexion_core::core::state::EdgeState;
+use reflexion_core::core::types::{EdgeId, EdgeKind};
+
+fn states(g: &ReflexionGraph, ids: &[EdgeId]) -> Vec<(EdgeState, i32)> {
+    ids.iter().map(|&id| (g.edge_state(id).unwrap(), g.edge_counter(id).unwrap())).collect()
+}
+
+#[test]
+fn incremental_edits_match_run_from_scratch() {
+    let mut g = ReflexionGraph::new();
+    let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+    let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+    let arch = EdgeBuilder::new(ui, service).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+
+    let page = NodeBuilder::implementation("LoginPage").add_to(&mut g).unwrap();
+    let users = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+    let e1 = EdgeBuilder::new(page, users).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    let e2 = EdgeBuilder::new(users, page).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    assert_eq!(g.edge_state(e1).unwrap(), EdgeState::Unmapped);
+
+    g.set_mapping_incremental(page, ui).unwrap();
+    g.set_mapping_incremental(users, service).unwrap();
+    let ids = [arch, e1, e2];
+    let incremental = states(&g, &ids);
+    g.run_from_scratch().unwrap();
+    assert_eq!(incremental, states(&g, &ids));
+    assert_eq!(incremental[0], (EdgeState::Convergent, 1));
+    assert_eq!(incremental[2].0, EdgeState::Divergent);
+
+    g.remove_impl_edge(e1).unwrap();
+    assert!(g.edge(e1).is_none());
+    assert_eq!(g.edge_state(arch).unwrap(), EdgeState::Absent);
+    assert_eq!(g.count_violations(), 3); //absent arch edge + divergent impl and propagated edge
+}
@@ -0,0 +1,86 @@
+// a model as an external extractor would write it (tests/data/layered_model.json)
+use reflexion_core::core::forbidden::ForbiddenRule;
+use reflexion_core::core::graph::ReflexionGraph;
+use reflexion_core::core::layers::Layering;
+use reflexion_core::core::mapping::MappingRule;
+use reflexion_core::core::model::ModelDocument;
+use reflexion_core::core::state::EdgeState;
+use reflexion_core::core::types::{EdgeKind, NodeKind, SubgraphKind};
+
+const MODEL: &str = include_str!("data/layered_model.json");
+
+#[test]
+fn extractor_model_is_imported_analysed_and_exported_with_results() {
+    let imported = ReflexionGraph::from_json(MODEL).unwrap();
+    let g = &imported.graph;
+    let id = |doc_id: u32| imported.node_ids[&doc_id];
+
+    assert_eq!(g.node_kind(id(30)).unwrap(), &NodeKind::custom("Repository"));
+    assert_eq!(g.node_kind(id(40)).unwrap(), &NodeKind::ImplementationNode);
+    assert_eq!(g.get_effective_arch_node(id(11)).unwrap(), Some(id(1)));
+
+    let state_of = |from: u32, to: u32| {
+        g.edges_in(SubgraphKind::Implementation)
+            .find(|e| e.from() == id(from) && e.to() == id(to))
+            .map(|e| e.state())
+            .unwrap()
+    };
+    assert_eq!(state_of(11, 20), EdgeState::Convergent);
+    assert_eq!(state_of(20, 21), EdgeState::Allowed);
+    assert_eq!(state_of(11, 30), EdgeState::Divergent);
+    assert_eq!(state_of(40, 20), EdgeState::Unmapped);
+
+    //export with results, read back as a plain document
+    let doc = ModelDocument::from_json(&g.to_json(true).unwrap()).unwrap();
+    let results = doc.results.as_ref().unwrap();
+    let service_db = results
+        .iter()
+        .find(|r| r.subgraph == SubgraphKind::Architecture && r.from == id(2) && r.to == id(3))
+        .unwrap();
+    assert_eq!((service_db.state, service_db.counter), (EdgeState::Absent, 0));
+    assert_eq!(results.iter().filter(|r| r.state == EdgeState::Divergent).count(), 2); //impl + propagated
+
+    //the export is a valid input again and gives the same analysis
+    let again = ReflexionGraph::from_model(&doc).unwrap().graph;
+    assert!(again.edges().eq(g.edges()));
+}
+
+#[test]
+fn schema_lists_every_document_field() {
+    let schema: serde_json::Value = serde_json::from_str(include_str!("../docs/model.schema.json")).unwrap();
+    //one of everything the example leaves out, so every table has a row
+    let imported = ReflexionGraph::from_json(MODEL).unwrap();
+    let id = |doc_id: u32| imported.node_ids[&doc_id];
+    let mut g = imported.graph;
+    g.add_edge_kind_subtype(EdgeKind::from("imports"), EdgeKind::depends_on()).unwrap();
+    g.restrict_mapping_kinds(id(3), [NodeKind::custom("Repository")]).unwrap();
+    g.add_mapping_rule(MappingRule::glob("ui/**", id(1))).unwrap();
+    g.add_forbidden_rule(ForbiddenRule::new(id(3), id(1))).unwrap();
+    g.set_layering(Layering::relaxed(vec![vec![id(1)], vec![id(2)], vec![id(3)]])).unwrap();
+    g.set_node_attr(id(1), "owner", "web").unwrap();
+    let first_arch_edge = g.edges_in(SubgraphKind::Architecture).next().unwrap().id();
+    g.set_edge_attr(first_arch_edge, "weight", 2).unwrap();
+    let doc = g.to_model(true);
+    let exported: serde_json::Value = serde_json::from_str(&doc.to_json().unwrap()).unwrap();
+
+    let props = |path: &str| -> Vec<String> {
+        let mut keys: Vec<String> = schema.pointer(path).unwrap().as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    };
+    let keys = |v: &serde_json::Value| -> Vec<String> {
+        let mut keys: Vec<String> = v.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    };
+    assert_eq!(keys(&exported), props("/properties"));
+    assert_eq!(keys(&exported["architecture"]), props("/$defs/subgraph/properties"));
+    assert_eq!(keys(&exported["architecture"]["nodes"][0]), props("/$defs/subgraph/properties/nodes/items/properties"));
+    assert_eq!(keys(&exported["architecture"]["edges"][0]), props("/$defs/edge/properties"));
+    assert_eq!(keys(&exported["mapping"][0]), props("/properties/mapping/items/properties"));
+    assert_eq!(keys(&exported["results"][0]), props("/properties/results/items/properties"));
+    for table in ["edge_kinds", "kind_restrictions", "mapping_rules", "forbidden"] {
+        assert_eq!(keys(&exported[table][0]), props(&format!("/properties/{}/items/properties", table)), "{}", table);
+    }
+    assert_eq!(keys(&exported["layering"]), props("/properties/layering/oneOf/1/properties"));
+}
@@ -0,0 +1,84 @@
+// UI -> Service -> DB, built and read through the public API only
+use reflexion_core::core::builder::{EdgeBuilder, NodeBuilder};
+use reflexion_core::core::graph::ReflexionGraph;
+use reflexion_core::core::layers::Layering;
+use reflexion_core::core::state::EdgeState;
+use reflexion_core::core::types::{EdgeKind, SubgraphKind};
+
+#[test]
+fn specified_layers_classify_convergent_divergent_and_absent() {
+    let mut g = ReflexionGraph::new();
+    let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+    let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+    let db = NodeBuilder::architecture("DB").add_to(&mut g).unwrap();
+    let ui_service = EdgeBuilder::new(ui, service).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+    let service_db = EdgeBuilder::new(service, db).kind(EdgeKind::calls()).architecture().add_to(&mut g).unwrap();
+
+    let page = NodeBuilder::implementation("LoginPage").add_to(&mut g).unwrap();
+    let users = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+    let repo = NodeBuilder::implementation("UserRepo").add_to(&mut g).unwrap();
+    g.set_mapping(page, ui).unwrap();
+    g.set_mapping(users, service).unwrap();
+    g.set_mapping(repo, db).unwrap();
+
+    let down = EdgeBuilder::new(page, users).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    let skip = EdgeBuilder::new(page, repo).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+
+    assert_eq!(g.edge_state(down).unwrap(), EdgeState::Convergent);
+    assert_eq!(g.edge_state(skip).unwrap(), EdgeState::Divergent);
+    assert_eq!(g.edge_state(ui_service).unwrap(), EdgeState::Convergent);
+    assert_eq!(g.edge_state(service_db).unwrap(), EdgeState::Absent);
+    assert_eq!(g.edge_counter(ui_service).unwrap(), 1);
+
+    let divergent: Vec<_> = g
+        .edges_with_state(EdgeState::Divergent)
+        .filter(|e| e.subgraph() == SubgraphKind::Implementation)
+        .map(|e| e.id())
+        .collect();
+    assert_eq!(divergent, vec![skip]);
+    assert_eq!(g.edges_in(SubgraphKind::Architecture).count(), 2);
+}
+
+#[test]
+fn strict_layering_without_explicit_edges() {
+    let mut g = ReflexionGraph::new();
+    let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+    let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+    let db = NodeBuilder::architecture("DB").add_to(&mut g).unwrap();
+    g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]]).with_kind(EdgeKind::calls()))
+        .unwrap();
+
+    let page = NodeBuilder::implementation("LoginPage").add_to(&mut g).unwrap();
+    let users = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+    g.set_mapping(page, ui).unwrap();
+    g.set_mapping(users, service).unwrap();
+
+    let down = EdgeBuilder::new(page, users).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    let up = EdgeBuilder::new(users, page).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    assert_eq!(g.edge_state(down).unwrap(), EdgeState::Allowed);
+    assert_eq!(g.edge_state(up).unwrap(), EdgeState::Divergent);
+}
+
+#[test]
+fn bare_strict_layering_allows_downward_calls() {
+    let mut g = ReflexionGraph::new();
+    let ui = NodeBuilder::architecture("UI").add_to(&mut g).unwrap();
+    let service = NodeBuilder::architecture("Service").add_to(&mut g).unwrap();
+    let db = NodeBuilder::architecture("DB").add_to(&mut g).unwrap();
+    g.set_layering(Layering::strict(vec![vec![ui], vec![service], vec![db]])).unwrap();
+
+    let page = NodeBuilder::implementation("LoginPage").add_to(&mut g).unwrap();
+    let users = NodeBuilder::implementation("UserService").add_to(&mut g).unwrap();
+    let repo = NodeBuilder::implementation("UserRepo").add_to(&mut g).unwrap();
+    g.set_mapping(page, ui).unwrap();
+    g.set_mapping(users, service).unwrap();
+    g.set_mapping(repo, db).unwrap();
+
+    let down = EdgeBuilder::new(page, users).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    let skip = EdgeBuilder::new(page, repo).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    let up = EdgeBuilder::new(repo, users).kind(EdgeKind::calls()).add_to(&mut g).unwrap();
+    assert_eq!(g.edge_state(down).unwrap(), EdgeState::Allowed);
+    assert_eq!(g.edge_state(skip).unwrap(), EdgeState::Divergent);
+    assert_eq!(g.edge_state(up).unwrap(), EdgeState::Divergent);
+    assert_eq!(g.count_violations(), 4);
+}